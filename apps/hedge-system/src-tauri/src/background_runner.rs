@@ -0,0 +1,272 @@
+use std::collections::HashMap;
+use std::future::Future;
+use std::sync::Arc;
+use std::time::Duration;
+
+use log::{error, info, warn};
+use tokio::sync::{watch, RwLock};
+
+/// 登録済みタスクの現在状態。`statuses()`が返し、`stop_server_graceful`の待機判定/デバッグ表示に使う。
+#[derive(Debug, Clone)]
+pub struct TaskStatus {
+    pub name: String,
+    pub restart_count: u32,
+    pub running: bool,
+}
+
+#[derive(Debug)]
+struct TaskEntry {
+    restart_count: u32,
+    running: bool,
+}
+
+/// 個々の`tokio::spawn`呼び出しをひとつのレジストリに集約し、
+/// パニック時の再起動（指数バックオフ付き）と一元的なシャットダウン通知を提供する。
+///
+/// `WSServerManager`はハートビート監視・パフォーマンス監視を`spawn`経由、受付ループと
+/// 各コネクションハンドラーを`spawn_connection`経由で起動し、個別の`JoinHandle`管理を持たない。
+#[derive(Debug, Clone)]
+pub struct BackgroundRunner {
+    tasks: Arc<RwLock<HashMap<String, TaskEntry>>>,
+    shutdown_tx: watch::Sender<bool>,
+    shutdown_rx: watch::Receiver<bool>,
+}
+
+impl BackgroundRunner {
+    pub fn new() -> Self {
+        let (shutdown_tx, shutdown_rx) = watch::channel(false);
+        Self {
+            tasks: Arc::new(RwLock::new(HashMap::new())),
+            shutdown_tx,
+            shutdown_rx,
+        }
+    }
+
+    /// 全タスクが購読するシャットダウンシグナルを発行する。
+    /// 受信側は`shutdown_receiver()`で購読し、`tokio::select!`で監視する。
+    pub fn signal_shutdown(&self) {
+        let _ = self.shutdown_tx.send(true);
+    }
+
+    /// 次回の`start`に備えてシャットダウンシグナルをリセットする。
+    pub fn reset(&self) {
+        let _ = self.shutdown_tx.send(false);
+    }
+
+    pub fn shutdown_receiver(&self) -> watch::Receiver<bool> {
+        self.shutdown_rx.clone()
+    }
+
+    pub fn is_shutting_down(&self) -> bool {
+        *self.shutdown_rx.borrow()
+    }
+
+    pub async fn statuses(&self) -> Vec<TaskStatus> {
+        self.tasks
+            .read()
+            .await
+            .iter()
+            .map(|(name, entry)| TaskStatus {
+                name: name.clone(),
+                restart_count: entry.restart_count,
+                running: entry.running,
+            })
+            .collect()
+    }
+
+    /// 名前付きの長寿命タスクを起動する。`make_future`が返すfutureがパニックすると、
+    /// シャットダウンが要求されていない限り指数バックオフ(最大30秒)で再起動する。
+    pub fn spawn<F, Fut>(&self, name: impl Into<String>, make_future: F) -> tokio::task::JoinHandle<()>
+    where
+        F: Fn() -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = ()> + Send + 'static,
+    {
+        let name = name.into();
+        let tasks = Arc::clone(&self.tasks);
+        let mut shutdown_rx = self.shutdown_rx.clone();
+
+        tokio::spawn(async move {
+            tasks.write().await.insert(
+                name.clone(),
+                TaskEntry {
+                    restart_count: 0,
+                    running: true,
+                },
+            );
+
+            let mut backoff = Duration::from_millis(500);
+            const MAX_BACKOFF: Duration = Duration::from_secs(30);
+
+            loop {
+                if *shutdown_rx.borrow() {
+                    break;
+                }
+
+                let fut = make_future();
+                let mut handle = tokio::spawn(fut);
+                let result = tokio::select! {
+                    result = &mut handle => result,
+                    _ = shutdown_rx.changed() => {
+                        info!("Task '{}' received shutdown signal, aborting in-flight iteration", name);
+                        // `tokio::select!`が降りるだけでは内側の`JoinHandle`はデタッチされ
+                        // バックグラウンドで走り続けてしまう。明示的にabortしてから
+                        // 完了を待つことで、同じclients/client_senders等のArcを握ったまま
+                        // 多重に生き残るのを防ぐ。
+                        handle.abort();
+                        let _ = handle.await;
+                        break;
+                    }
+                };
+
+                if *shutdown_rx.borrow() {
+                    break;
+                }
+
+                match result {
+                    Ok(()) => {
+                        // タスクが正常終了した場合（ループを抜けた等）も監視対象から外す
+                        info!("Task '{}' exited normally", name);
+                        break;
+                    }
+                    Err(join_err) if join_err.is_panic() => {
+                        let mut tasks_lock = tasks.write().await;
+                        let restart_count = tasks_lock
+                            .get_mut(&name)
+                            .map(|entry| {
+                                entry.restart_count += 1;
+                                entry.restart_count
+                            })
+                            .unwrap_or(1);
+                        drop(tasks_lock);
+
+                        error!(
+                            "Task '{}' panicked (restart #{}), retrying in {:?}",
+                            name, restart_count, backoff
+                        );
+                        tokio::time::sleep(backoff).await;
+                        backoff = std::cmp::min(backoff * 2, MAX_BACKOFF);
+                        continue;
+                    }
+                    Err(join_err) => {
+                        warn!("Task '{}' was cancelled: {}", name, join_err);
+                        break;
+                    }
+                }
+            }
+
+            if let Some(entry) = tasks.write().await.get_mut(&name) {
+                entry.running = false;
+            }
+        })
+    }
+
+    /// 接続ごとのハンドラー用スポーン。パニックしても再起動はしない
+    /// （切断されたコネクションをそのまま復活させる意味がないため）が、同じレジストリに
+    /// 登録され、終了時にレジストリから外れる。ランナーはこのタスクをシャットダウンシグナルで
+    /// abortしないため、シグナルの監視は渡された`future`自身が行う。
+    pub fn spawn_connection<Fut>(&self, name: impl Into<String>, future: Fut) -> tokio::task::JoinHandle<()>
+    where
+        Fut: Future<Output = ()> + Send + 'static,
+    {
+        let name = name.into();
+        let tasks = Arc::clone(&self.tasks);
+
+        tokio::spawn(async move {
+            tasks.write().await.insert(
+                name.clone(),
+                TaskEntry {
+                    restart_count: 0,
+                    running: true,
+                },
+            );
+
+            future.await;
+
+            tasks.write().await.remove(&name);
+        })
+    }
+}
+
+impl Default for BackgroundRunner {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicU32, Ordering};
+
+    #[tokio::test]
+    async fn panicking_task_restarts_with_incrementing_restart_count() {
+        let runner = BackgroundRunner::new();
+        let call_count = Arc::new(AtomicU32::new(0));
+        let call_count_for_task = Arc::clone(&call_count);
+
+        let handle = runner.spawn("flaky", move || {
+            let call_count = Arc::clone(&call_count_for_task);
+            async move {
+                let attempt = call_count.fetch_add(1, Ordering::SeqCst);
+                if attempt < 2 {
+                    panic!("simulated failure on attempt {}", attempt);
+                }
+            }
+        });
+
+        // 2回パニックして3回目に正常終了し、レジストリ上でrunning=falseになるまで待つ
+        tokio::time::timeout(Duration::from_secs(5), async {
+            loop {
+                let statuses = runner.statuses().await;
+                if let Some(status) = statuses.iter().find(|s| s.name == "flaky") {
+                    if !status.running && status.restart_count == 2 {
+                        break;
+                    }
+                }
+                tokio::time::sleep(Duration::from_millis(20)).await;
+            }
+        })
+        .await
+        .expect("task should finish restarting within timeout");
+
+        let _ = handle.await;
+    }
+
+    #[tokio::test]
+    async fn signal_shutdown_stops_a_long_running_task_via_abort() {
+        let runner = BackgroundRunner::new();
+
+        let handle = runner.spawn("long_runner", || async {
+            // shutdownが来ない限り自発的には終わらないタスクを模す
+            loop {
+                tokio::time::sleep(Duration::from_secs(60)).await;
+            }
+        });
+
+        // レジストリに登録されるまで待つ
+        tokio::time::timeout(Duration::from_secs(1), async {
+            loop {
+                if runner.statuses().await.iter().any(|s| s.name == "long_runner" && s.running) {
+                    break;
+                }
+                tokio::time::sleep(Duration::from_millis(10)).await;
+            }
+        })
+        .await
+        .expect("task should register itself");
+
+        runner.signal_shutdown();
+
+        tokio::time::timeout(Duration::from_secs(2), handle)
+            .await
+            .expect("outer spawn should stop promptly after abort")
+            .expect("outer spawn task must not panic");
+
+        let statuses = runner.statuses().await;
+        let status = statuses
+            .iter()
+            .find(|s| s.name == "long_runner")
+            .expect("task should remain registered");
+        assert!(!status.running, "task should be marked not running after shutdown");
+    }
+}
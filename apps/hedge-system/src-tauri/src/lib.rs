@@ -1,15 +1,43 @@
 use tauri::menu::{MenuBuilder, MenuItemBuilder, SubmenuBuilder};
-use tauri::{Manager, AppHandle, Emitter};
+use tauri::{AppHandle, Emitter, Manager};
 
+mod background_runner;
+mod local_ipc;
+mod metrics;
+mod persistence;
+mod protocol;
+mod rate_limiter;
+mod system_health;
+mod tls;
+mod tray;
+mod updater;
 mod websocket;
 
-// アップデートチェックコマンド
+// アップデートチェックコマンド。チェック〜署名検証〜インストールまでをRust側(updater)で
+// 完結させ、結果は`update-available`/`update-ready`/`update-error`イベントで通知する。
 #[tauri::command]
 async fn check_for_updates(app: AppHandle) -> Result<String, String> {
     log::info!("Manual update check triggered via command");
-    // フロントエンドで処理するため、イベントを発行
-    app.emit("manual-update-check", ()).map_err(|e| e.to_string())?;
-    Ok("Update check initiated".to_string())
+    updater::check_and_install(&app).await?;
+    Ok("Update check completed".to_string())
+}
+
+/// 終了経路（メニュー/トレイ/OSからのウィンドウ全終了）がどれであっても同じクリーンアップを
+/// 通るよう、`RunEvent::ExitRequested`のハンドラからのみ呼び出す。WebSocketサーバー・
+/// ローカルIPCサーバーを受付停止→クローズフレーム送信→有限待機という手順で畳んでから
+/// プロセスを終了するので、ブローカー側の注文が中途半端な状態で取り残されるのを防げる。
+async fn shutdown_and_exit(app: &AppHandle) {
+    log::info!("Exit requested, shutting down WebSocket server gracefully before quitting");
+
+    let state = app.state::<websocket::WSServerManager>();
+    if let Err(e) = state.stop_server_graceful(None).await {
+        log::error!("Graceful WebSocket shutdown failed: {}", e);
+    }
+    if let Err(e) = state.stop_local_ipc_server().await {
+        log::error!("Failed to stop local IPC server during shutdown: {}", e);
+    }
+
+    std::process::exit(0);
 }
 
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
@@ -22,10 +50,19 @@ pub fn run() {
       check_for_updates,
       websocket::start_websocket_server,
       websocket::stop_websocket_server,
+      websocket::stop_websocket_server_graceful,
       websocket::get_websocket_server_status,
       websocket::get_websocket_clients,
       websocket::disconnect_websocket_client,
-      websocket::update_websocket_config
+      websocket::send_command_to_client,
+      websocket::query_ea_events,
+      websocket::replay_events,
+      websocket::pause_websocket_accepting,
+      websocket::resume_websocket_accepting,
+      websocket::is_websocket_accepting,
+      websocket::update_websocket_config,
+      websocket::start_local_ipc_server,
+      websocket::stop_local_ipc_server
     ])
     .setup(|app| {
       if cfg!(debug_assertions) {
@@ -55,23 +92,33 @@ pub fn run() {
 
       app.set_menu(menu)?;
 
+      // クライアント接続/切断・サーバー状態変化を全ウィンドウへemitできるよう、
+      // `WSServerManager`へ`AppHandle`を注入する
+      app.state::<websocket::WSServerManager>().set_app_handle(app.handle().clone());
+
+      // システムトレイ: メインウィンドウを開かなくてもサーバー状態の確認・起動・停止ができるようにする
+      tray::build_tray(app.handle())?;
+
+      // バックグラウンドでの定期アップデートチェックと、フロントエンド/メニューからの
+      // 即時再チェック要求(`updater::RECHECK_EVENT`)の受付を開始する
+      updater::register_recheck_listener(app.handle());
+      updater::spawn_periodic_check(app.handle().clone(), updater::DEFAULT_POLL_INTERVAL);
+
       // メニューイベントハンドラー
       app.on_menu_event(move |app, event| {
         match event.id().as_ref() {
           "check_updates" => {
             log::info!("Menu: Check for updates clicked");
-            
-            // メインウィンドウに対してイベントを送信
-            if let Some(window) = app.get_webview_window("main") {
-              if let Err(e) = window.emit("manual-update-check", ()) {
-                log::error!("Failed to emit update check event: {}", e);
-              }
-            } else {
-              // ウィンドウが見つからない場合は、アプリ全体にイベントを送信
-              if let Err(e) = app.emit("manual-update-check", ()) {
-                log::error!("Failed to emit update check event: {}", e);
+
+            // メニューイベントハンドラーは同期コンテキストのため、チェック〜インストールは
+            // 別タスクとしてスポーンする。結果は update-available/update-ready/update-error
+            // イベント経由でフロントエンドに届く。
+            let app_handle = app.clone();
+            tauri::async_runtime::spawn(async move {
+              if let Err(e) = updater::check_and_install(&app_handle).await {
+                log::error!("Update check from menu failed: {}", e);
               }
-            }
+            });
           }
           "about" => {
             log::info!("Menu: About clicked");
@@ -81,7 +128,7 @@ pub fn run() {
           }
           "quit" => {
             log::info!("Menu: Quit clicked");
-            std::process::exit(0);
+            app.exit(0);
           }
           _ => {}
         }
@@ -89,6 +136,17 @@ pub fn run() {
 
       Ok(())
     })
-    .run(tauri::generate_context!())
-    .expect("error while running tauri application");
+    .build(tauri::generate_context!())
+    .expect("error while building tauri application")
+    .run(|app_handle, event| {
+      // `app.exit(0)`(メニュー/トレイ)、ウィンドウが全て閉じられた場合、OSからの終了要求の
+      // いずれも、ここでまとめて同じグレースフルシャットダウンを通す。
+      if let tauri::RunEvent::ExitRequested { api, .. } = event {
+        api.prevent_exit();
+        let app_handle = app_handle.clone();
+        tauri::async_runtime::spawn(async move {
+          shutdown_and_exit(&app_handle).await;
+        });
+      }
+    });
 }
@@ -0,0 +1,109 @@
+use std::io;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+use tokio::io::{AsyncRead, AsyncWrite, ReadBuf};
+
+#[cfg(unix)]
+use tokio::net::{UnixListener, UnixStream};
+#[cfg(windows)]
+use tokio::net::windows::named_pipe::{NamedPipeServer, ServerOptions};
+
+/// Unixドメインソケット(Unix)・名前付きパイプ(Windows)のどちらで接続していても、
+/// 呼び出し側からは同じストリームとして扱うためのenum。`tls::MaybeTlsStream`と同様、
+/// `accept_async`は`AsyncRead + AsyncWrite + Unpin`であれば型を問わないため、
+/// ここでどちらの内部型にも読み書きを委譲するだけでよい。
+pub enum LocalIpcStream {
+    #[cfg(unix)]
+    Unix(UnixStream),
+    #[cfg(windows)]
+    Pipe(NamedPipeServer),
+}
+
+impl AsyncRead for LocalIpcStream {
+    fn poll_read(self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &mut ReadBuf<'_>) -> Poll<io::Result<()>> {
+        match self.get_mut() {
+            #[cfg(unix)]
+            LocalIpcStream::Unix(s) => Pin::new(s).poll_read(cx, buf),
+            #[cfg(windows)]
+            LocalIpcStream::Pipe(s) => Pin::new(s).poll_read(cx, buf),
+        }
+    }
+}
+
+impl AsyncWrite for LocalIpcStream {
+    fn poll_write(self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &[u8]) -> Poll<io::Result<usize>> {
+        match self.get_mut() {
+            #[cfg(unix)]
+            LocalIpcStream::Unix(s) => Pin::new(s).poll_write(cx, buf),
+            #[cfg(windows)]
+            LocalIpcStream::Pipe(s) => Pin::new(s).poll_write(cx, buf),
+        }
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        match self.get_mut() {
+            #[cfg(unix)]
+            LocalIpcStream::Unix(s) => Pin::new(s).poll_flush(cx),
+            #[cfg(windows)]
+            LocalIpcStream::Pipe(s) => Pin::new(s).poll_flush(cx),
+        }
+    }
+
+    fn poll_shutdown(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        match self.get_mut() {
+            #[cfg(unix)]
+            LocalIpcStream::Unix(s) => Pin::new(s).poll_shutdown(cx),
+            #[cfg(windows)]
+            LocalIpcStream::Pipe(s) => Pin::new(s).poll_shutdown(cx),
+        }
+    }
+}
+
+/// プラットフォームネイティブなローカルIPCエンドポイントの待受。Unixでは`UnixListener`を
+/// そのまま使い回せるが、名前付きパイプはコネクションごとにサーバーインスタンスを
+/// 作り直す必要があるため、Windowsでは`accept`のたびに次のインスタンスを用意してから
+/// `connect().await`で1接続を受け付ける。
+pub enum LocalIpcListener {
+    #[cfg(unix)]
+    Unix(UnixListener),
+    #[cfg(windows)]
+    Pipe { path: String, next: NamedPipeServer },
+}
+
+impl LocalIpcListener {
+    pub fn bind(path: &str) -> io::Result<Self> {
+        #[cfg(unix)]
+        {
+            // 前回異常終了時に残ったソケットファイルが残っていると`bind`が失敗するため先に掃除する
+            let _ = std::fs::remove_file(path);
+            return Ok(Self::Unix(UnixListener::bind(path)?));
+        }
+        #[cfg(windows)]
+        {
+            let next = ServerOptions::new().first_pipe_instance(true).create(path)?;
+            return Ok(Self::Pipe { path: path.to_string(), next });
+        }
+        #[cfg(not(any(unix, windows)))]
+        {
+            let _ = path;
+            Err(io::Error::new(io::ErrorKind::Unsupported, "local IPC transport is not supported on this platform"))
+        }
+    }
+
+    pub async fn accept(&mut self) -> io::Result<LocalIpcStream> {
+        match self {
+            #[cfg(unix)]
+            Self::Unix(listener) => {
+                let (stream, _addr) = listener.accept().await?;
+                Ok(LocalIpcStream::Unix(stream))
+            }
+            #[cfg(windows)]
+            Self::Pipe { path, next } => {
+                next.connect().await?;
+                let connected = std::mem::replace(next, ServerOptions::new().create(path.as_str())?);
+                Ok(LocalIpcStream::Pipe(connected))
+            }
+        }
+    }
+}
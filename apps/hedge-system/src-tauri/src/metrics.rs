@@ -0,0 +1,167 @@
+use std::fmt::Write as _;
+use std::sync::Arc;
+
+use log::{debug, error, info, warn};
+use serde::{Deserialize, Serialize};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpListener;
+use tokio::sync::{watch, RwLock};
+
+/// メトリクスの出力先。`Prometheus`は`/metrics`へのpull、`InfluxDb`はline protocolでの
+/// 定期pushに対応する。パフォーマンス監視の10秒ティックを再利用して更新・送出する。
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(tag = "kind")]
+pub enum MetricsExportConfig {
+    Prometheus {
+        bind_host: String,
+        bind_port: u16,
+    },
+    InfluxDb {
+        /// 例: "http://localhost:8086"
+        url: String,
+        bucket: String,
+        token: String,
+    },
+}
+
+/// パフォーマンス監視ティックごとに更新される、エクスポート用のメトリクススナップショット
+#[derive(Debug, Clone, Default)]
+pub struct MetricsSnapshot {
+    pub connected_clients: usize,
+    pub peak_connections: usize,
+    pub avg_latency_ms: f64,
+    pub messages_per_second: f64,
+    pub error_rate: f64,
+    pub total_messages_received: u64,
+    pub total_messages_sent: u64,
+    /// (client_id, latency_ms) のペア。Prometheusではクライアント単位のゲージとして出力する（ヒストグラムは出力しない）
+    pub client_latencies_ms: Vec<(String, f64)>,
+}
+
+/// Prometheusのテキスト形式(exposition format)にレンダリングする
+pub fn render_prometheus(snapshot: &MetricsSnapshot) -> String {
+    let mut out = String::new();
+
+    let _ = writeln!(out, "# HELP hedge_system_connected_clients Currently connected EA clients");
+    let _ = writeln!(out, "# TYPE hedge_system_connected_clients gauge");
+    let _ = writeln!(out, "hedge_system_connected_clients {}", snapshot.connected_clients);
+
+    let _ = writeln!(out, "# HELP hedge_system_peak_connections Highest number of concurrent connections observed");
+    let _ = writeln!(out, "# TYPE hedge_system_peak_connections gauge");
+    let _ = writeln!(out, "hedge_system_peak_connections {}", snapshot.peak_connections);
+
+    let _ = writeln!(out, "# HELP hedge_system_avg_latency_ms Mean of live clients' RTT EWMA, in milliseconds");
+    let _ = writeln!(out, "# TYPE hedge_system_avg_latency_ms gauge");
+    let _ = writeln!(out, "hedge_system_avg_latency_ms {}", snapshot.avg_latency_ms);
+
+    let _ = writeln!(out, "# HELP hedge_system_messages_per_second Inbound message rate");
+    let _ = writeln!(out, "# TYPE hedge_system_messages_per_second gauge");
+    let _ = writeln!(out, "hedge_system_messages_per_second {}", snapshot.messages_per_second);
+
+    let _ = writeln!(out, "# HELP hedge_system_error_rate Percentage of messages that resulted in an error");
+    let _ = writeln!(out, "# TYPE hedge_system_error_rate gauge");
+    let _ = writeln!(out, "hedge_system_error_rate {}", snapshot.error_rate);
+
+    let _ = writeln!(out, "# HELP hedge_system_messages_received_total Total messages received since server start");
+    let _ = writeln!(out, "# TYPE hedge_system_messages_received_total counter");
+    let _ = writeln!(out, "hedge_system_messages_received_total {}", snapshot.total_messages_received);
+
+    let _ = writeln!(out, "# HELP hedge_system_messages_sent_total Total messages sent since server start");
+    let _ = writeln!(out, "# TYPE hedge_system_messages_sent_total counter");
+    let _ = writeln!(out, "hedge_system_messages_sent_total {}", snapshot.total_messages_sent);
+
+    let _ = writeln!(out, "# HELP hedge_system_client_latency_ms Per-client RTT EWMA, in milliseconds");
+    let _ = writeln!(out, "# TYPE hedge_system_client_latency_ms gauge");
+    for (client_id, latency_ms) in &snapshot.client_latencies_ms {
+        let _ = writeln!(out, "hedge_system_client_latency_ms{{client_id=\"{}\"}} {}", client_id, latency_ms);
+    }
+
+    out
+}
+
+/// InfluxDB line protocolにエンコードする（measurement: `hedge_system_performance`）
+pub fn render_influx_line_protocol(snapshot: &MetricsSnapshot) -> String {
+    format!(
+        "hedge_system_performance connected_clients={}i,peak_connections={}i,avg_latency_ms={},messages_per_second={},error_rate={},total_messages_received={}i,total_messages_sent={}i",
+        snapshot.connected_clients,
+        snapshot.peak_connections,
+        snapshot.avg_latency_ms,
+        snapshot.messages_per_second,
+        snapshot.error_rate,
+        snapshot.total_messages_received,
+        snapshot.total_messages_sent,
+    )
+}
+
+/// `InfluxDb`設定に従い、最新スナップショットをline protocolでpushする
+pub async fn push_influx(url: &str, bucket: &str, token: &str, snapshot: &MetricsSnapshot) -> Result<(), String> {
+    let body = render_influx_line_protocol(snapshot);
+    let endpoint = format!("{}/api/v2/write?bucket={}&precision=s", url.trim_end_matches('/'), bucket);
+
+    let client = reqwest::Client::new();
+    let response = client
+        .post(&endpoint)
+        .header("Authorization", format!("Token {}", token))
+        .body(body)
+        .send()
+        .await
+        .map_err(|e| format!("Failed to reach InfluxDB at {}: {}", endpoint, e))?;
+
+    if !response.status().is_success() {
+        return Err(format!("InfluxDB write rejected with status {}", response.status()));
+    }
+
+    Ok(())
+}
+
+/// `Prometheus`設定に従い、`/metrics`にテキスト形式を返す最小限のHTTPエンドポイントを立てる。
+/// シャットダウンシグナルを受けるまで待受を続ける。
+pub async fn serve_prometheus(
+    bind_host: String,
+    bind_port: u16,
+    snapshot: Arc<RwLock<MetricsSnapshot>>,
+    mut shutdown_rx: watch::Receiver<bool>,
+) {
+    let addr = format!("{}:{}", bind_host, bind_port);
+    let listener = match TcpListener::bind(&addr).await {
+        Ok(listener) => listener,
+        Err(e) => {
+            error!("Failed to bind Prometheus metrics endpoint on {}: {}", addr, e);
+            return;
+        }
+    };
+
+    info!("Prometheus metrics endpoint listening on http://{}/metrics", addr);
+
+    loop {
+        tokio::select! {
+            accepted = listener.accept() => {
+                let Ok((mut stream, _)) = accepted else { continue };
+                let snapshot = Arc::clone(&snapshot);
+
+                tokio::spawn(async move {
+                    let mut buf = [0u8; 1024];
+                    // リクエスト行だけ読めれば十分（パス/メソッドは問わず常にメトリクスを返す）
+                    if stream.read(&mut buf).await.is_err() {
+                        return;
+                    }
+
+                    let body = render_prometheus(&*snapshot.read().await);
+                    let response = format!(
+                        "HTTP/1.1 200 OK\r\nContent-Type: text/plain; version=0.0.4\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                        body.len(),
+                        body
+                    );
+
+                    if let Err(e) = stream.write_all(response.as_bytes()).await {
+                        warn!("Failed to write Prometheus metrics response: {}", e);
+                    }
+                });
+            }
+            _ = shutdown_rx.changed() => {
+                debug!("Prometheus metrics endpoint received shutdown signal");
+                break;
+            }
+        }
+    }
+}
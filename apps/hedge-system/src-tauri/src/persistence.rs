@@ -0,0 +1,390 @@
+use chrono::{DateTime, Utc};
+use log::{error, info};
+use std::fmt::Write as _;
+use serde::{Deserialize, Serialize};
+use sqlx::sqlite::{SqlitePool, SqlitePoolOptions};
+use sqlx::Row;
+
+/// `WSServerConfig.persistence`で有効化するSQLiteセッション永続化の設定。
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct PersistenceConfig {
+    pub db_path: String,
+    /// この秒数以内の再接続であれば、直前のセッション状態を復元する
+    pub resume_window_seconds: u64,
+    /// セッション・メトリクス行をこの日数より古くなったら掃除する
+    pub retention_days: u64,
+}
+
+impl Default for PersistenceConfig {
+    fn default() -> Self {
+        Self {
+            db_path: "hedge-system-sessions.db".to_string(),
+            resume_window_seconds: 60,
+            retention_days: 30,
+        }
+    }
+}
+
+/// 再接続時に復元するセッションのスナップショット
+#[derive(Debug, Clone)]
+pub struct StoredSession {
+    pub account: String,
+    pub pubkey: String,
+    pub last_client_id: String,
+    pub disconnected_at: DateTime<Utc>,
+    pub pending_messages: Vec<String>,
+}
+
+/// `events`テーブルの1行。`query_ea_events`/`replay_events`からフロントエンドへそのまま返す。
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StoredEvent {
+    pub id: i64,
+    pub client_id: String,
+    pub account: Option<String>,
+    pub msg_type: String,
+    pub raw_json: String,
+    pub received_at: DateTime<Utc>,
+}
+
+/// クライアント状態(`ClientConnection`)とバッファ済みメッセージをSQLiteへ永続化し、
+/// `resume_window`以内の再接続でEAの直前セッションを復元できるようにする。
+#[derive(Debug, Clone)]
+pub struct SessionStore {
+    pool: SqlitePool,
+}
+
+impl SessionStore {
+    pub async fn connect(config: &PersistenceConfig) -> Result<Self, sqlx::Error> {
+        let url = format!("sqlite://{}?mode=rwc", config.db_path);
+        let pool = SqlitePoolOptions::new()
+            .max_connections(5)
+            .connect(&url)
+            .await?;
+
+        let store = Self { pool };
+        store.run_migrations().await?;
+        Ok(store)
+    }
+
+    async fn run_migrations(&self) -> Result<(), sqlx::Error> {
+        sqlx::query(
+            "CREATE TABLE IF NOT EXISTS sessions (
+                account TEXT PRIMARY KEY,
+                pubkey TEXT NOT NULL,
+                last_client_id TEXT NOT NULL,
+                connected_at TEXT NOT NULL,
+                disconnected_at TEXT
+            )",
+        )
+        .execute(&self.pool)
+        .await?;
+
+        sqlx::query(
+            "CREATE TABLE IF NOT EXISTS pending_messages (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                account TEXT NOT NULL,
+                message TEXT NOT NULL,
+                created_at TEXT NOT NULL
+            )",
+        )
+        .execute(&self.pool)
+        .await?;
+
+        sqlx::query(
+            "CREATE TABLE IF NOT EXISTS performance_snapshots (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                captured_at TEXT NOT NULL,
+                total_connections INTEGER NOT NULL,
+                peak_connections INTEGER NOT NULL,
+                avg_latency_ms REAL NOT NULL,
+                error_rate REAL NOT NULL
+            )",
+        )
+        .execute(&self.pool)
+        .await?;
+
+        // 監査・再生用の追記専用イベントログ
+        sqlx::query(
+            "CREATE TABLE IF NOT EXISTS events (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                client_id TEXT NOT NULL,
+                account TEXT,
+                msg_type TEXT NOT NULL,
+                raw_json TEXT NOT NULL,
+                received_at TEXT NOT NULL
+            )",
+        )
+        .execute(&self.pool)
+        .await?;
+        sqlx::query("CREATE INDEX IF NOT EXISTS idx_events_client_id ON events (client_id)")
+            .execute(&self.pool)
+            .await?;
+
+        // 接続ライフサイクル（認証・切断・ハートビート欠落）の監査ログ
+        sqlx::query(
+            "CREATE TABLE IF NOT EXISTS connections (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                client_id TEXT NOT NULL,
+                account TEXT,
+                event TEXT NOT NULL,
+                occurred_at TEXT NOT NULL
+            )",
+        )
+        .execute(&self.pool)
+        .await?;
+
+        info!("SQLite session store migrations applied at startup");
+        Ok(())
+    }
+
+    /// EA接続時(認証成功後)にセッション行をupsertする
+    pub async fn upsert_session(&self, account: &str, pubkey: &str, client_id: &str) -> Result<(), sqlx::Error> {
+        sqlx::query(
+            "INSERT INTO sessions (account, pubkey, last_client_id, connected_at, disconnected_at)
+             VALUES (?1, ?2, ?3, ?4, NULL)
+             ON CONFLICT(account) DO UPDATE SET
+                pubkey = excluded.pubkey,
+                last_client_id = excluded.last_client_id,
+                connected_at = excluded.connected_at,
+                disconnected_at = NULL",
+        )
+        .bind(account)
+        .bind(pubkey)
+        .bind(client_id)
+        .bind(Utc::now().to_rfc3339())
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    /// EA切断時に`disconnected_at`を記録し、`resume_window`内の再接続判定に使う
+    pub async fn mark_disconnected(&self, account: &str) -> Result<(), sqlx::Error> {
+        sqlx::query("UPDATE sessions SET disconnected_at = ?1 WHERE account = ?2")
+            .bind(Utc::now().to_rfc3339())
+            .bind(account)
+            .execute(&self.pool)
+            .await?;
+        Ok(())
+    }
+
+    /// `resume_window`以内に切断されたセッションがあれば、未配信メッセージとともに返す
+    pub async fn find_resumable_session(
+        &self,
+        account: &str,
+        resume_window_seconds: u64,
+    ) -> Result<Option<StoredSession>, sqlx::Error> {
+        let Some(row) = sqlx::query(
+            "SELECT account, pubkey, last_client_id, disconnected_at FROM sessions WHERE account = ?1",
+        )
+        .bind(account)
+        .fetch_optional(&self.pool)
+        .await?
+        else {
+            return Ok(None);
+        };
+
+        let disconnected_at: Option<String> = row.try_get("disconnected_at")?;
+        let Some(disconnected_at) = disconnected_at else {
+            return Ok(None); // まだ接続中、もしくは初回接続
+        };
+        let disconnected_at = DateTime::parse_from_rfc3339(&disconnected_at)
+            .map(|dt| dt.with_timezone(&Utc))
+            .map_err(|e| sqlx::Error::Decode(Box::new(e)))?;
+
+        if (Utc::now() - disconnected_at).num_seconds() as u64 > resume_window_seconds {
+            return Ok(None); // resume_windowを過ぎている
+        }
+
+        let pending_messages = self.take_pending_messages(account).await?;
+
+        Ok(Some(StoredSession {
+            account: row.try_get("account")?,
+            pubkey: row.try_get("pubkey")?,
+            last_client_id: row.try_get("last_client_id")?,
+            disconnected_at,
+            pending_messages,
+        }))
+    }
+
+    /// 配信できなかったメッセージをアカウント単位でバッファする
+    pub async fn buffer_pending_message(&self, account: &str, message: &str) -> Result<(), sqlx::Error> {
+        sqlx::query("INSERT INTO pending_messages (account, message, created_at) VALUES (?1, ?2, ?3)")
+            .bind(account)
+            .bind(message)
+            .bind(Utc::now().to_rfc3339())
+            .execute(&self.pool)
+            .await?;
+        Ok(())
+    }
+
+    /// バッファ済みメッセージを取り出し、テーブルからは削除する（再送後は不要なため）
+    async fn take_pending_messages(&self, account: &str) -> Result<Vec<String>, sqlx::Error> {
+        let rows = sqlx::query("SELECT id, message FROM pending_messages WHERE account = ?1 ORDER BY id ASC")
+            .bind(account)
+            .fetch_all(&self.pool)
+            .await?;
+
+        let messages = rows.iter().map(|r| r.get::<String, _>("message")).collect();
+
+        sqlx::query("DELETE FROM pending_messages WHERE account = ?1")
+            .bind(account)
+            .execute(&self.pool)
+            .await?;
+
+        Ok(messages)
+    }
+
+    /// パフォーマンス監視ティックから定期的に呼ばれるメトリクススナップショット保存
+    pub async fn record_performance_snapshot(
+        &self,
+        total_connections: u64,
+        peak_connections: usize,
+        avg_latency_ms: f64,
+        error_rate: f64,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query(
+            "INSERT INTO performance_snapshots (captured_at, total_connections, peak_connections, avg_latency_ms, error_rate)
+             VALUES (?1, ?2, ?3, ?4, ?5)",
+        )
+        .bind(Utc::now().to_rfc3339())
+        .bind(total_connections as i64)
+        .bind(peak_connections as i64)
+        .bind(avg_latency_ms)
+        .bind(error_rate)
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    /// `retention_days`より古いセッション・スナップショット行を削除する
+    pub async fn prune_expired(&self, retention_days: u64) {
+        let cutoff = (Utc::now() - chrono::Duration::days(retention_days as i64)).to_rfc3339();
+
+        if let Err(e) = sqlx::query("DELETE FROM sessions WHERE disconnected_at IS NOT NULL AND disconnected_at < ?1")
+            .bind(&cutoff)
+            .execute(&self.pool)
+            .await
+        {
+            error!("Failed to prune expired sessions: {}", e);
+        }
+
+        if let Err(e) = sqlx::query("DELETE FROM performance_snapshots WHERE captured_at < ?1")
+            .bind(&cutoff)
+            .execute(&self.pool)
+            .await
+        {
+            error!("Failed to prune expired performance snapshots: {}", e);
+        }
+
+        if let Err(e) = sqlx::query("DELETE FROM events WHERE received_at < ?1")
+            .bind(&cutoff)
+            .execute(&self.pool)
+            .await
+        {
+            error!("Failed to prune expired events: {}", e);
+        }
+
+        if let Err(e) = sqlx::query("DELETE FROM connections WHERE occurred_at < ?1")
+            .bind(&cutoff)
+            .execute(&self.pool)
+            .await
+        {
+            error!("Failed to prune expired connection events: {}", e);
+        }
+    }
+
+    /// EAから受信したイベント(`EaEvent`)を監査・再生用に追記する
+    pub async fn record_event(
+        &self,
+        client_id: &str,
+        account: Option<&str>,
+        msg_type: &str,
+        raw_json: &str,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query(
+            "INSERT INTO events (client_id, account, msg_type, raw_json, received_at) VALUES (?1, ?2, ?3, ?4, ?5)",
+        )
+        .bind(client_id)
+        .bind(account)
+        .bind(msg_type)
+        .bind(raw_json)
+        .bind(Utc::now().to_rfc3339())
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    /// 接続ライフサイクル上の出来事(`"AUTH"`/`"DISCONNECT"`/`"HEARTBEAT_GAP"`)を記録する
+    pub async fn record_connection_event(
+        &self,
+        client_id: &str,
+        account: Option<&str>,
+        event: &str,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query("INSERT INTO connections (client_id, account, event, occurred_at) VALUES (?1, ?2, ?3, ?4)")
+            .bind(client_id)
+            .bind(account)
+            .bind(event)
+            .bind(Utc::now().to_rfc3339())
+            .execute(&self.pool)
+            .await?;
+        Ok(())
+    }
+
+    /// 監査・再生のため、クライアント単位でイベントを時系列順に問い合わせる
+    pub async fn query_events(
+        &self,
+        client_id: &str,
+        from: Option<DateTime<Utc>>,
+        to: Option<DateTime<Utc>>,
+        msg_types: Option<&[String]>,
+    ) -> Result<Vec<StoredEvent>, sqlx::Error> {
+        let mut sql = String::from(
+            "SELECT id, client_id, account, msg_type, raw_json, received_at FROM events WHERE client_id = ?",
+        );
+        if from.is_some() {
+            sql.push_str(" AND received_at >= ?");
+        }
+        if to.is_some() {
+            sql.push_str(" AND received_at <= ?");
+        }
+        if let Some(types) = msg_types {
+            if !types.is_empty() {
+                let placeholders = types.iter().map(|_| "?").collect::<Vec<_>>().join(", ");
+                let _ = write!(sql, " AND msg_type IN ({})", placeholders);
+            }
+        }
+        sql.push_str(" ORDER BY id ASC");
+
+        let mut query = sqlx::query(&sql).bind(client_id);
+        if let Some(from) = from {
+            query = query.bind(from.to_rfc3339());
+        }
+        if let Some(to) = to {
+            query = query.bind(to.to_rfc3339());
+        }
+        if let Some(types) = msg_types {
+            for t in types {
+                query = query.bind(t);
+            }
+        }
+
+        let rows = query.fetch_all(&self.pool).await?;
+
+        rows.into_iter()
+            .map(|row| {
+                let received_at: String = row.try_get("received_at")?;
+                Ok(StoredEvent {
+                    id: row.try_get("id")?,
+                    client_id: row.try_get("client_id")?,
+                    account: row.try_get("account")?,
+                    msg_type: row.try_get("msg_type")?,
+                    raw_json: row.try_get("raw_json")?,
+                    received_at: DateTime::parse_from_rfc3339(&received_at)
+                        .map(|dt| dt.with_timezone(&Utc))
+                        .map_err(|e| sqlx::Error::Decode(Box::new(e)))?,
+                })
+            })
+            .collect()
+    }
+}
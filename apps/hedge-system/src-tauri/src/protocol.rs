@@ -0,0 +1,71 @@
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::websocket::EAInfo;
+
+/// EAから届くリクエストの種別。内部タグ`type`でシリアライズされる。
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(tag = "type")]
+pub enum RequestKind {
+    Heartbeat,
+    /// ed25519ハンドシェイク後の再認証要求（現状のハンドシェイクは別経路で完結するため予約）
+    Authenticate,
+    RegisterEa { ea_info: EAInfo },
+    OrderUpdate { payload: serde_json::Value },
+    Subscribe { channels: Vec<String> },
+    /// OPENED/CLOSED/ERROR/PRICE/PONG/INFOなど、既存のEAイベント系メッセージ
+    EaEvent {
+        event_type: String,
+        payload: serde_json::Value,
+    },
+}
+
+/// EAからの全リクエストはこのコンテナでラップされ、`request_id`でレスポンスと相関する。
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct RequestContainer {
+    pub request_id: Uuid,
+    #[serde(flatten)]
+    pub kind: RequestKind,
+}
+
+/// サーバーからの応答種別。`request_id`は常に起点のリクエストを参照する。
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(tag = "type")]
+pub enum ResponseKind {
+    HeartbeatAck,
+    RegisterEaAck,
+    OrderUpdateAck,
+    SubscribeAck,
+    EventAck,
+    Error { code: String, message: String },
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ResponseContainer {
+    pub request_id: Uuid,
+    #[serde(flatten)]
+    pub kind: ResponseKind,
+}
+
+impl ResponseContainer {
+    pub fn error(request_id: Uuid, code: impl Into<String>, message: impl Into<String>) -> Self {
+        Self {
+            request_id,
+            kind: ResponseKind::Error {
+                code: code.into(),
+                message: message.into(),
+            },
+        }
+    }
+
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(self).unwrap_or_else(|e| {
+            // シリアライズ自体が失敗することは通常ないが、フォールバックとして
+            // 最低限のエラーJSONを手組みで返す
+            format!(
+                "{{\"request_id\":\"{}\",\"type\":\"Error\",\"code\":\"SERIALIZATION_FAILED\",\"message\":\"{}\"}}",
+                self.request_id, e
+            )
+        })
+    }
+}
@@ -0,0 +1,48 @@
+use std::sync::Arc;
+use tokio::sync::Mutex;
+use tokio::time::Instant;
+
+/// シンプルなトークンバケット。`capacity`個まで溜められ、`refill_per_second`個/秒で補充される。
+/// 接続受付の急増(フラッド)やクライアント単位のメッセージ連打を一定レートに抑えるために使う。
+#[derive(Debug)]
+pub struct TokenBucket {
+    inner: Mutex<BucketState>,
+    capacity: f64,
+    refill_per_second: f64,
+}
+
+#[derive(Debug)]
+struct BucketState {
+    tokens: f64,
+    last_refill: Instant,
+}
+
+impl TokenBucket {
+    pub fn new(capacity: u32, refill_per_second: u32) -> Arc<Self> {
+        Arc::new(Self {
+            inner: Mutex::new(BucketState {
+                tokens: capacity as f64,
+                last_refill: Instant::now(),
+            }),
+            capacity: capacity as f64,
+            refill_per_second: refill_per_second as f64,
+        })
+    }
+
+    /// トークンが1個以上あれば消費してtrueを返す。枯渇していればfalse（＝レート超過）。
+    pub async fn try_acquire(&self) -> bool {
+        let mut state = self.inner.lock().await;
+
+        let now = Instant::now();
+        let elapsed = now.duration_since(state.last_refill).as_secs_f64();
+        state.tokens = (state.tokens + elapsed * self.refill_per_second).min(self.capacity);
+        state.last_refill = now;
+
+        if state.tokens >= 1.0 {
+            state.tokens -= 1.0;
+            true
+        } else {
+            false
+        }
+    }
+}
@@ -0,0 +1,70 @@
+use std::sync::Arc;
+
+use sysinfo::{Pid, ProcessRefreshKind, RefreshKind, System};
+use tokio::sync::Mutex;
+use tokio::time::Instant;
+
+/// sysinfoが定める最小CPU計測間隔より短い間隔で繰り返し計測しても差分が取れず不正確になるため、
+/// スナップショットはこの間隔でのみ実際にリフレッシュし、それ以外はキャッシュ値を返す。
+const MIN_REFRESH_INTERVAL: std::time::Duration = sysinfo::MINIMUM_CPU_UPDATE_INTERVAL;
+
+/// 自プロセスの実メモリ・CPU使用率と、サーバーが把握している接続数をまとめたスナップショット
+#[derive(Debug, Clone, Default)]
+pub struct SystemHealthSnapshot {
+    pub memory_rss_bytes: u64,
+    pub cpu_usage_percent: f32,
+    pub open_connections: usize,
+}
+
+/// `System`を1インスタンスだけキャッシュし、`MIN_REFRESH_INTERVAL`を尊重しながら
+/// 自プロセス(pid)のメモリ・CPUを計測する。`WSServerManager`に1つだけ持たせて使い回す。
+#[derive(Debug)]
+pub struct SystemHealthMonitor {
+    inner: Mutex<MonitorState>,
+}
+
+#[derive(Debug)]
+struct MonitorState {
+    system: System,
+    pid: Pid,
+    last_refresh: Instant,
+    cached: SystemHealthSnapshot,
+}
+
+impl SystemHealthMonitor {
+    pub fn new() -> Arc<Self> {
+        let pid = Pid::from_u32(std::process::id());
+        let system = System::new_with_specifics(
+            RefreshKind::new().with_processes(ProcessRefreshKind::everything()),
+        );
+
+        Arc::new(Self {
+            inner: Mutex::new(MonitorState {
+                system,
+                pid,
+                // 起動直後の最初の呼び出しで即座にリフレッシュされるよう、間隔分だけ過去にずらす
+                last_refresh: Instant::now() - MIN_REFRESH_INTERVAL,
+                cached: SystemHealthSnapshot::default(),
+            }),
+        })
+    }
+
+    /// 自プロセスのRSS/CPU使用率を計測し、呼び出し側から渡された接続数と合わせて返す。
+    /// 最小計測間隔内の連続呼び出しはキャッシュされた値をそのまま返す。
+    pub async fn snapshot(&self, open_connections: usize) -> SystemHealthSnapshot {
+        let mut state = self.inner.lock().await;
+
+        if state.last_refresh.elapsed() >= MIN_REFRESH_INTERVAL {
+            let pid = state.pid;
+            state.system.refresh_process(pid);
+            if let Some(process) = state.system.process(pid) {
+                state.cached.memory_rss_bytes = process.memory();
+                state.cached.cpu_usage_percent = process.cpu_usage();
+            }
+            state.last_refresh = Instant::now();
+        }
+
+        state.cached.open_connections = open_connections;
+        state.cached.clone()
+    }
+}
@@ -0,0 +1,84 @@
+use std::io::BufReader;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::task::{Context, Poll};
+
+use serde::{Deserialize, Serialize};
+use tokio::io::{AsyncRead, AsyncWrite, ReadBuf};
+use tokio::net::TcpStream;
+use tokio_rustls::server::TlsStream;
+use tokio_rustls::TlsAcceptor;
+
+/// `WSServerConfig.tls`で設定する、TLS終端に使う証明書チェーンと秘密鍵のファイルパス
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct TlsConfig {
+    pub cert_path: String,
+    pub key_path: String,
+}
+
+/// 証明書/秘密鍵ファイルを読み込んでパースし、`TlsAcceptor`を構築する。
+/// `start_server`と`update_websocket_config`の双方がこれを呼んで設定の妥当性を事前検証する。
+pub fn build_tls_acceptor(config: &TlsConfig) -> Result<TlsAcceptor, String> {
+    let cert_file = std::fs::File::open(&config.cert_path)
+        .map_err(|e| format!("Failed to open TLS cert at {}: {}", config.cert_path, e))?;
+    let key_file = std::fs::File::open(&config.key_path)
+        .map_err(|e| format!("Failed to open TLS key at {}: {}", config.key_path, e))?;
+
+    let certs: Vec<_> = rustls_pemfile::certs(&mut BufReader::new(cert_file))
+        .collect::<Result<_, _>>()
+        .map_err(|e| format!("Failed to parse TLS cert chain at {}: {}", config.cert_path, e))?;
+    if certs.is_empty() {
+        return Err(format!("No certificates found in {}", config.cert_path));
+    }
+
+    let key = rustls_pemfile::private_key(&mut BufReader::new(key_file))
+        .map_err(|e| format!("Failed to parse TLS private key at {}: {}", config.key_path, e))?
+        .ok_or_else(|| format!("No private key found in {}", config.key_path))?;
+
+    let server_config = rustls::ServerConfig::builder()
+        .with_no_client_auth()
+        .with_single_cert(certs, key)
+        .map_err(|e| format!("Invalid TLS certificate/key pair: {}", e))?;
+
+    Ok(TlsAcceptor::from(Arc::new(server_config)))
+}
+
+/// 平文TCP接続とTLS終端後の接続を、呼び出し側からは同じストリームとして扱うためのenum。
+/// `tokio_tungstenite::accept_async`は`AsyncRead + AsyncWrite + Unpin`であれば型を問わないため、
+/// ここでどちらの内部型にも読み書きを委譲するだけでよい。
+pub enum MaybeTlsStream {
+    Plain(TcpStream),
+    Tls(Box<TlsStream<TcpStream>>),
+}
+
+impl AsyncRead for MaybeTlsStream {
+    fn poll_read(self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &mut ReadBuf<'_>) -> Poll<std::io::Result<()>> {
+        match self.get_mut() {
+            MaybeTlsStream::Plain(s) => Pin::new(s).poll_read(cx, buf),
+            MaybeTlsStream::Tls(s) => Pin::new(s).poll_read(cx, buf),
+        }
+    }
+}
+
+impl AsyncWrite for MaybeTlsStream {
+    fn poll_write(self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &[u8]) -> Poll<std::io::Result<usize>> {
+        match self.get_mut() {
+            MaybeTlsStream::Plain(s) => Pin::new(s).poll_write(cx, buf),
+            MaybeTlsStream::Tls(s) => Pin::new(s).poll_write(cx, buf),
+        }
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<std::io::Result<()>> {
+        match self.get_mut() {
+            MaybeTlsStream::Plain(s) => Pin::new(s).poll_flush(cx),
+            MaybeTlsStream::Tls(s) => Pin::new(s).poll_flush(cx),
+        }
+    }
+
+    fn poll_shutdown(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<std::io::Result<()>> {
+        match self.get_mut() {
+            MaybeTlsStream::Plain(s) => Pin::new(s).poll_shutdown(cx),
+            MaybeTlsStream::Tls(s) => Pin::new(s).poll_shutdown(cx),
+        }
+    }
+}
@@ -0,0 +1,119 @@
+use std::time::Duration;
+
+use log::{error, info};
+use tauri::menu::MenuBuilder;
+use tauri::menu::MenuItemBuilder;
+use tauri::tray::{TrayIcon, TrayIconBuilder};
+use tauri::{AppHandle, Manager};
+
+use crate::websocket::WSServerManager;
+
+/// トレイのツールチップをポーリングで更新する間隔。ハートビート/パフォーマンス監視と
+/// 同程度の粒度で十分であり、クライアント接続/切断ごとに専用の通知経路は設けない。
+const TRAY_REFRESH_INTERVAL: Duration = Duration::from_secs(2);
+
+/// `run()`の`setup`から呼び出し、トレイアイコン・メニューを構築してステータスポーリングを起動する。
+/// メニューの各項目は`start_websocket_server`/`stop_websocket_server`コマンドと同じ
+/// `WSServerManager::start_server`/`stop_server`を直接呼び出す。
+pub fn build_tray(app: &AppHandle) -> tauri::Result<()> {
+    let start_item = MenuItemBuilder::with_id("tray_start_server", "サーバーを起動").build(app)?;
+    let stop_item = MenuItemBuilder::with_id("tray_stop_server", "サーバーを停止").build(app)?;
+    let show_item = MenuItemBuilder::with_id("tray_show_window", "ウィンドウを表示").build(app)?;
+    let check_updates_item = MenuItemBuilder::with_id("tray_check_updates", "アップデートを確認").build(app)?;
+    let quit_item = MenuItemBuilder::with_id("tray_quit", "終了").build(app)?;
+
+    let menu = MenuBuilder::new(app)
+        .item(&start_item)
+        .item(&stop_item)
+        .separator()
+        .item(&show_item)
+        .item(&check_updates_item)
+        .separator()
+        .item(&quit_item)
+        .build()?;
+
+    let mut tray_builder = TrayIconBuilder::with_id("hedge-system-tray")
+        .tooltip("Hedge System — WebSocket server stopped")
+        .menu(&menu)
+        .show_menu_on_left_click(true)
+        .on_menu_event(move |app, event| match event.id().as_ref() {
+            "tray_start_server" => {
+                let app_handle = app.clone();
+                tauri::async_runtime::spawn(async move {
+                    let state = app_handle.state::<WSServerManager>();
+                    if let Err(e) = state.start_server().await {
+                        error!("Failed to start WebSocket server from tray: {}", e);
+                    }
+                });
+            }
+            "tray_stop_server" => {
+                let app_handle = app.clone();
+                tauri::async_runtime::spawn(async move {
+                    let state = app_handle.state::<WSServerManager>();
+                    if let Err(e) = state.stop_server().await {
+                        error!("Failed to stop WebSocket server from tray: {}", e);
+                    }
+                });
+            }
+            "tray_show_window" => {
+                if let Some(window) = app.get_webview_window("main") {
+                    let _ = window.show();
+                    let _ = window.set_focus();
+                }
+            }
+            "tray_check_updates" => {
+                let app_handle = app.clone();
+                tauri::async_runtime::spawn(async move {
+                    if let Err(e) = crate::updater::check_and_install(&app_handle).await {
+                        error!("Update check from tray failed: {}", e);
+                    }
+                });
+            }
+            "tray_quit" => {
+                info!("Tray: Quit clicked");
+                // `app.exit`は`RunEvent::ExitRequested`を発火させ、メニューの「終了」と
+                // 同じグレースフルシャットダウン経路(`lib.rs::shutdown_and_exit`)を通る
+                app.exit(0);
+            }
+            _ => {}
+        });
+
+    if let Some(icon) = app.default_window_icon() {
+        tray_builder = tray_builder.icon(icon.clone());
+    }
+
+    let tray = tray_builder.build(app)?;
+
+    spawn_status_poller(app.clone(), tray);
+
+    Ok(())
+}
+
+/// `WSServerManager`の状態・接続クライアント数を定期的に読み、トレイのツールチップへ反映する。
+/// 稼働中/停止中でツールチップの文言を変え、稼働中は接続数も表示することで
+/// メインウィンドウを開かなくても一目で状態が分かるようにする。
+fn spawn_status_poller(app: AppHandle, tray: TrayIcon) {
+    tauri::async_runtime::spawn(async move {
+        let mut interval = tokio::time::interval(TRAY_REFRESH_INTERVAL);
+
+        loop {
+            interval.tick().await;
+
+            let state = app.state::<WSServerManager>();
+            let status = state.get_status().await;
+
+            let tooltip = if status.is_running {
+                format!(
+                    "Hedge System — running ({}://{}:{}), {} client(s) connected",
+                    status.protocol, status.host, status.port, status.connected_clients
+                )
+            } else {
+                "Hedge System — WebSocket server stopped".to_string()
+            };
+
+            if let Err(e) = tray.set_tooltip(Some(&tooltip)) {
+                error!("Failed to update tray tooltip: {}", e);
+            }
+        }
+    });
+}
@@ -0,0 +1,138 @@
+use std::time::Duration;
+
+use log::{debug, error, info};
+use serde::Serialize;
+use tauri::{AppHandle, Emitter, Listener};
+use tauri_plugin_updater::{Update, UpdaterExt};
+
+/// アップデートバンドルの署名検証に使うed25519/minisign公開鍵。サーバーが返す
+/// アップデート情報は信頼できない入力として扱い、インストール前に必ずこの鍵で検証する。
+/// 実運用では配布時に本物の公開鍵へ差し替える。
+const UPDATE_PUBKEY: &str = "untrusted comment: minisign public key\nRWTPLACEHOLDERKEYPLACEHOLDERKEYPLACEHOLDERKEYPLACE";
+
+/// バックグラウンドの定期アップデートチェックのデフォルト間隔。無人稼働のトレーディング
+/// 環境でも現実的な頻度で更新を検知できるよう6時間ごとにした。
+pub const DEFAULT_POLL_INTERVAL: Duration = Duration::from_secs(6 * 60 * 60);
+
+/// フロントエンド/メニューがこのイベントを発行すると、次回の定期チェックを待たずに
+/// 即座の再チェックを依頼できる。
+pub const RECHECK_EVENT: &str = "app://recheck-update";
+
+#[derive(Debug, Clone, Serialize)]
+pub struct UpdateAvailablePayload {
+    pub version: String,
+    pub notes: Option<String>,
+    /// バンドルの署名検証が済んでいるか。`update-available`は`check()`直後の通知であり、
+    /// ダウンロード・検証自体は`update-ready`/`update-error`が確定するまで行われないため常にfalse
+    pub signature_verified: bool,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct UpdateReadyPayload {
+    pub version: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct UpdateErrorPayload {
+    pub message: String,
+}
+
+/// `check()`のみを行う。更新の有無や対象バージョンの判断は、エンドポイントがサーバー側で
+/// `{{target}}`/`{{current_version}}`を解決した上で返すレスポンス（フェーズドロールアウト中なら
+/// 204 No Content、対象なら更新情報のJSON）に完全に委ねる — ここでは静的なフィードやバージョン
+/// 比較は一切行わない。ダウンロード・インストールは行わず、提供されていれば`update-available`を
+/// 発行するだけなので、定期ポーリングや単なる通知目的で安全に呼べる。
+async fn check_only(app: &AppHandle) -> Result<Option<Update>, String> {
+    let updater = app
+        .updater_builder()
+        .pubkey(UPDATE_PUBKEY)
+        .build()
+        .map_err(|e| format!("Failed to initialize updater: {}", e))?;
+
+    match updater.check().await {
+        Ok(Some(update)) => {
+            info!("Update {} available", update.version);
+            let _ = app.emit(
+                "update-available",
+                UpdateAvailablePayload {
+                    version: update.version.clone(),
+                    notes: update.body.clone(),
+                    signature_verified: false,
+                },
+            );
+            Ok(Some(update))
+        }
+        Ok(None) => {
+            debug!("No update available (server did not offer one for this rollout)");
+            Ok(None)
+        }
+        Err(e) => {
+            let message = format!("Update check failed: {}", e);
+            error!("{}", message);
+            let _ = app.emit("update-error", UpdateErrorPayload { message: message.clone() });
+            Err(message)
+        }
+    }
+}
+
+/// アップデートチェック〜インストールまでをRust側で完結させる。メニュー/コマンドなど、
+/// ユーザーが明示的にアップデートを要求した経路から呼ぶ。ダウンロードしたバンドルは
+/// `UPDATE_PUBKEY`でed25519/minisign署名検証され、検証に失敗した場合は
+/// `download_and_install`自体がエラーを返しインストールは行われない。
+pub async fn check_and_install(app: &AppHandle) -> Result<(), String> {
+    let Some(update) = check_only(app).await? else {
+        return Ok(());
+    };
+
+    info!("Downloading and verifying signature for update {} before install", update.version);
+
+    match update.download_and_install(|_chunk_length, _content_length| {}, || {}).await {
+        Ok(()) => {
+            info!("Update {} downloaded, signature verified, and installed", update.version);
+            let _ = app.emit("update-ready", UpdateReadyPayload { version: update.version.clone() });
+            Ok(())
+        }
+        Err(e) => {
+            // 署名検証・ダウンロードのいずれが失敗しても、ここに到達した時点で
+            // 既存のインストールには一切手を付けていない
+            let message = format!("Update download or signature verification failed: {}", e);
+            error!("{}", message);
+            let _ = app.emit("update-error", UpdateErrorPayload { message: message.clone() });
+            Err(message)
+        }
+    }
+}
+
+/// `interval`ごとにバックグラウンドで`check_only`を呼ぶ。無人稼働中のトレーディング環境で
+/// 勝手にバイナリを差し替えないよう、ダウンロード・インストールは行わない
+/// （実際のインストールは`check_and_install`を使う明示的な経路に委ねる）。
+/// サーバーが新バージョンを提供した場合のみ`update-available`イベントが発行される。
+pub fn spawn_periodic_check(app: AppHandle, interval: Duration) {
+    tauri::async_runtime::spawn(async move {
+        let mut ticker = tokio::time::interval(interval);
+        ticker.tick().await; // 起動直後の1回は間引き、まず`interval`待ってから最初のチェックを行う
+
+        loop {
+            ticker.tick().await;
+            debug!("Running scheduled background update check");
+            if let Err(e) = check_only(&app).await {
+                error!("Scheduled update check failed: {}", e);
+            }
+        }
+    });
+}
+
+/// `RECHECK_EVENT`のグローバルリスナーを登録する。フロントエンドやメニューはこのイベントを
+/// 発行するだけで、次回の定期ポーリングを待たずに即座の再チェックを依頼できる。
+pub fn register_recheck_listener(app: &AppHandle) {
+    let app_handle = app.clone();
+    app.listen(RECHECK_EVENT, move |_event| {
+        let app_handle = app_handle.clone();
+        tauri::async_runtime::spawn(async move {
+            info!("Received {} event, running an immediate update check", RECHECK_EVENT);
+            if let Err(e) = check_only(&app_handle).await {
+                error!("Immediate update check failed: {}", e);
+            }
+        });
+    });
+}
@@ -1,18 +1,42 @@
+use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering};
 use std::sync::Arc;
 use std::time::Duration;
-use tauri::State;
-use tokio::sync::{Mutex, RwLock, mpsc};
-use tokio::time::{Instant, interval};
+use tauri::{AppHandle, Emitter, State};
+use tokio::io::{AsyncRead, AsyncWrite};
+use tokio::sync::{Mutex, RwLock, mpsc, oneshot, watch};
+use tokio::time::{Instant, interval, timeout};
 use tokio_tungstenite::{
     accept_async,
-    tungstenite::{Message, protocol::CloseFrame},
+    tungstenite::{Message, protocol::{CloseFrame, frame::coding::CloseCode}},
 };
+use base64::{engine::general_purpose::STANDARD as BASE64, Engine as _};
+use ed25519_dalek::{Signature, Verifier, VerifyingKey};
 use futures_util::{SinkExt, StreamExt};
 use log::{debug, error, info, warn};
+use rand::RngCore;
 use uuid::Uuid;
 
+use crate::background_runner::BackgroundRunner;
+use crate::local_ipc::LocalIpcListener;
+use crate::metrics::{MetricsExportConfig, MetricsSnapshot};
+use crate::persistence::{PersistenceConfig, SessionStore, StoredEvent};
+use crate::protocol::{RequestContainer, RequestKind, ResponseContainer, ResponseKind};
+use crate::rate_limiter::TokenBucket;
+use crate::system_health::SystemHealthMonitor;
+use crate::tls::{MaybeTlsStream, TlsConfig};
+
+/// EAとの接続直後に要求する署名応答の待ち時間
+const AUTH_HANDSHAKE_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// 1クライアントがこの回数だけレート制限に抵触したら、悪質とみなして強制切断する
+const MAX_MESSAGE_RATE_VIOLATIONS: u32 = 20;
+
+/// 自プロセスのCPU使用率がこれを超えたら「張り付いている」とみなして最適化提案に含める
+const CPU_PEGGED_THRESHOLD_PERCENT: f32 = 80.0;
+
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct WSServerState {
     pub is_running: bool,
@@ -24,6 +48,38 @@ pub struct WSServerState {
     pub errors: u64,
     pub uptime_seconds: u64,
     pub started_at: Option<String>,
+    /// リスナーが`"ws"`(平文)と`"wss"`(TLS終端)のどちらで稼働しているか
+    pub protocol: String,
+}
+
+/// フロントエンドへ配信するイベント名。`get_websocket_clients`をポーリングしなくても
+/// 接続ログをリアルタイムに描画できるよう、`app.emit`で全ウィンドウへブロードキャストする。
+pub const EVENT_CLIENT_CONNECTED: &str = "websocket://client-connected";
+pub const EVENT_CLIENT_DISCONNECTED: &str = "websocket://client-disconnected";
+pub const EVENT_SERVER_STATE_CHANGED: &str = "websocket://server-state-changed";
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ClientConnectedPayload {
+    pub client_id: String,
+    pub remote_addr: String,
+    pub transport: String,
+    pub timestamp: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ClientDisconnectedPayload {
+    pub client_id: String,
+    pub reason: String,
+    pub timestamp: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ServerStateChangedPayload {
+    pub is_running: bool,
+    pub protocol: String,
+    pub host: String,
+    pub port: u16,
+    pub timestamp: String,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -39,6 +95,9 @@ pub struct ClientConnection {
     pub connection_quality: String, // "EXCELLENT", "GOOD", "POOR"
     pub message_buffer_size: usize,
     pub last_message_timestamp: String,
+    pub pubkey: Option<String>, // 認証に成功したEAのed25519公開鍵（base64）
+    /// このクライアントがどのトランスポートで接続しているか（"tcp"または"local_ipc"）
+    pub transport: String,
 }
 
 #[derive(Debug)]
@@ -52,7 +111,19 @@ pub struct MessageBuffer {
 pub struct ConnectionPool {
     active_connections: Arc<RwLock<HashMap<String, Arc<Connection>>>>,
     message_buffers: Arc<RwLock<HashMap<String, MessageBuffer>>>,
-    performance_metrics: Arc<RwLock<PerformanceMetrics>>,
+    /// 総接続数・同時接続ピーク・処理済みメッセージ数・エラー数は、PRICE tick等で
+    /// 多数のEAから届くメッセージのホットパス上でclients/poolマップ全体の書き込みロックを
+    /// 取らずに済むよう、すべてAtomicで保持しRelaxedで更新する。
+    total_connections: AtomicU64,
+    peak_connections: AtomicUsize,
+    messages_processed: AtomicU64,
+    messages_sent: AtomicU64,
+    total_errors: AtomicU64,
+    /// RTT EWMAから算出した平均レイテンシ。更新頻度がPingインターバル単位と低いためRwLockのままでよい
+    avg_latency_ms: Arc<RwLock<f64>>,
+    /// クライアントごとのRTT(ms)のEWMA(alpha≈0.2)。単発の処理スパイクに引きずられず、
+    /// ネットワーク遅延の傾向を`connection_quality`に反映させるために保持する。
+    latency_ewma: Arc<RwLock<HashMap<String, f64>>>,
 }
 
 #[derive(Debug)]
@@ -85,10 +156,31 @@ pub struct EAInfo {
 pub struct WSServerConfig {
     pub port: u16,
     pub host: String,
-    pub auth_token: String,
+    /// チャレンジ署名に含めるサーバー識別子（ドメイン分離用）
+    pub server_id: String,
+    /// 接続を許可するEAのed25519公開鍵（base64）のアロウリスト。account名をキーとする
+    pub ea_public_keys: HashMap<String, String>,
     pub max_connections: usize,
     pub heartbeat_interval_seconds: u64,
     pub connection_timeout_seconds: u64,
+    pub shutdown_timeout_seconds: u64,
+    /// 受付ループのトークンバケットが1秒あたりに許容する新規接続数
+    pub max_connections_per_second: u32,
+    /// クライアントごとのトークンバケットが1秒あたりに許容するメッセージ数
+    pub max_messages_per_second: u32,
+    /// 設定時、クライアントセッション・バッファ済みメッセージをSQLiteへ永続化し、
+    /// `resume_window_seconds`以内の再接続でEAの直前セッションを復元する
+    pub persistence: Option<PersistenceConfig>,
+    /// 設定時、パフォーマンス監視ティックのたびにPrometheus向け`/metrics`を更新するか、
+    /// InfluxDBへline protocolをpushする
+    pub metrics_export: Option<MetricsExportConfig>,
+    /// 自プロセスのRSSがこのしきい値(MB)を超えたら`optimize_websocket_performance`が警告する
+    pub memory_warning_threshold_mb: u64,
+    /// 設定時、`wss://`として起動する前にTLS証明書・秘密鍵を読み込み`TlsAcceptor`を構築する
+    pub tls: Option<TlsConfig>,
+    /// `true`の場合、`start_server`はサーバーを起動せずエラーを返す（vaultwardenのWEBSOCKET_DISABLEDに倣う、デフォルトfalse）
+    #[serde(default)]
+    pub disabled: bool,
 }
 
 #[derive(Debug)]
@@ -96,11 +188,33 @@ pub struct WSServerManager {
     pub state: Arc<Mutex<WSServerState>>,
     pub clients: Arc<RwLock<HashMap<String, ClientConnection>>>,
     pub config: Arc<RwLock<WSServerConfig>>,
-    pub server_handle: Arc<Mutex<Option<tokio::task::JoinHandle<()>>>>,
     pub started_at: Arc<Mutex<Option<Instant>>>,
     pub connection_pool: Arc<ConnectionPool>,
-    pub heartbeat_handle: Arc<Mutex<Option<tokio::task::JoinHandle<()>>>>,
-    pub performance_monitor_handle: Arc<Mutex<Option<tokio::task::JoinHandle<()>>>>,
+    /// ハートビート監視・パフォーマンス監視・受付ループ・各コネクションハンドラーを
+    /// すべてこのランナー経由でスポーンする。個別の`JoinHandle`は保持しない。
+    runner: BackgroundRunner,
+    /// `config.persistence`が設定されている間のみ`Some`になるSQLiteセッションストア
+    session_store: Arc<RwLock<Option<Arc<SessionStore>>>>,
+    /// `pause_accepting`/`resume_accepting`で切り替える、受付ループの新規接続可否フラグ
+    accepting: Arc<AtomicBool>,
+    /// パフォーマンス監視ティックごとに更新される、メトリクスエクスポート用の最新スナップショット
+    metrics_snapshot: Arc<RwLock<MetricsSnapshot>>,
+    /// 認証済みクライアントごとの送信チャンネル。`send_command`がコマンドをユニキャストするのに使う
+    client_senders: Arc<RwLock<HashMap<String, mpsc::UnboundedSender<Message>>>>,
+    /// requestId -> (宛先client_id, 応答を待つoneshot送信側)。応答は`handle_connection`の
+    /// メッセージループが`requestId`一致で見つけ次第ルーティングする
+    pending_commands: Arc<RwLock<HashMap<String, (String, oneshot::Sender<Result<serde_json::Value, String>>)>>>,
+    /// 自プロセスの実メモリ・CPU使用率を計測するキャッシュ付きモニター
+    system_health: Arc<SystemHealthMonitor>,
+    /// `start_local_ipc_server`実行中のみ`Some`になる、待受ループ専用のシャットダウン通知。
+    /// TCP側の`runner`シャットダウンとはライフサイクルが独立しており、どちらか一方だけを
+    /// 起動・停止できる。
+    local_ipc_shutdown: Arc<Mutex<Option<watch::Sender<bool>>>>,
+    /// `set_app_handle`経由で`setup`時に一度だけ注入される。クライアント接続/切断・
+    /// サーバー状態変化を全ウィンドウへ`app.emit`でブロードキャストするのに使う。
+    /// 起動直後のイベントは購読側がまだいない可能性があるため`Option`で、未注入の間は
+    /// 静かにemitを諦める。
+    app_handle: Arc<std::sync::Mutex<Option<AppHandle>>>,
 }
 
 impl ConnectionPool {
@@ -108,10 +222,16 @@ impl ConnectionPool {
         Self {
             active_connections: Arc::new(RwLock::new(HashMap::new())),
             message_buffers: Arc::new(RwLock::new(HashMap::new())),
-            performance_metrics: Arc::new(RwLock::new(PerformanceMetrics::default())),
+            total_connections: AtomicU64::new(0),
+            peak_connections: AtomicUsize::new(0),
+            messages_processed: AtomicU64::new(0),
+            messages_sent: AtomicU64::new(0),
+            total_errors: AtomicU64::new(0),
+            avg_latency_ms: Arc::new(RwLock::new(0.0)),
+            latency_ewma: Arc::new(RwLock::new(HashMap::new())),
         }
     }
-    
+
     pub async fn add_connection(&self, id: String, connection: Arc<Connection>) {
         self.active_connections.write().await.insert(id.clone(), connection);
         self.message_buffers.write().await.insert(id, MessageBuffer {
@@ -119,57 +239,128 @@ impl ConnectionPool {
             max_size: 1000,
             created_at: Instant::now(),
         });
-        
-        // Update performance metrics
-        let mut metrics = self.performance_metrics.write().await;
-        metrics.total_connections += 1;
+
+        self.total_connections.fetch_add(1, Ordering::Relaxed);
         let current_count = self.active_connections.read().await.len();
-        if current_count > metrics.peak_connections {
-            metrics.peak_connections = current_count;
-        }
+        self.peak_connections.fetch_max(current_count, Ordering::Relaxed);
     }
-    
+
     pub async fn remove_connection(&self, id: &str) {
         self.active_connections.write().await.remove(id);
         self.message_buffers.write().await.remove(id);
+        self.remove_latency(id).await;
     }
-    
+
     pub async fn get_connection(&self, id: &str) -> Option<Arc<Connection>> {
         self.active_connections.read().await.get(id).cloned()
     }
-    
-    pub async fn broadcast_message(&self, message: &str) -> Result<usize, String> {
-        let connections = self.active_connections.read().await;
-        let mut sent_count = 0;
-        
-        for connection in connections.values() {
-            if let Ok(sender) = connection.sender.try_lock() {
-                if sender.send(Message::Text(message.to_string())).is_ok() {
-                    sent_count += 1;
-                }
-            }
-        }
-        
-        Ok(sent_count)
+
+    /// メッセージ受信をホットパスでカウントする。ロックを一切取らないRelaxedなアトミック加算のみ。
+    pub fn record_message_received(&self) {
+        self.messages_processed.fetch_add(1, Ordering::Relaxed);
     }
-    
-    pub async fn get_performance_metrics(&self) -> PerformanceMetrics {
-        self.performance_metrics.read().await.clone()
+
+    /// メッセージ送信をホットパスでカウントする。ロックを一切取らないRelaxedなアトミック加算のみ。
+    pub fn record_message_sent(&self) {
+        self.messages_sent.fetch_add(1, Ordering::Relaxed);
     }
-    
-    pub async fn update_latency(&self, client_id: &str, latency_ms: f64) {
-        if let Some(connection) = self.get_connection(client_id).await {
-            let mut info = connection.info.write().await;
-            info.latency_ms = Some(latency_ms);
-            info.connection_quality = if latency_ms < 50.0 {
-                "EXCELLENT".to_string()
-            } else if latency_ms < 100.0 {
-                "GOOD".to_string()
-            } else {
-                "POOR".to_string()
+
+    /// メッセージ処理エラーをホットパスでカウントする。ロックを一切取らないRelaxedなアトミック加算のみ。
+    pub fn record_error(&self) {
+        self.total_errors.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn messages_received_count(&self) -> u64 {
+        self.messages_processed.load(Ordering::Relaxed)
+    }
+
+    pub fn messages_sent_count(&self) -> u64 {
+        self.messages_sent.load(Ordering::Relaxed)
+    }
+
+    pub fn total_errors_count(&self) -> u64 {
+        self.total_errors.load(Ordering::Relaxed)
+    }
+
+    /// アトミックカウンタと、呼び出し側が計測した`uptime_seconds`から算出した
+    /// `messages_per_second`/`error_rate`をまとめて返す。
+    pub async fn get_performance_metrics(&self, uptime_seconds: u64) -> PerformanceMetrics {
+        let total_connections = self.total_connections.load(Ordering::Relaxed);
+        let peak_connections = self.peak_connections.load(Ordering::Relaxed);
+        let messages_processed = self.messages_processed.load(Ordering::Relaxed);
+        let total_errors = self.total_errors.load(Ordering::Relaxed);
+        let avg_latency_ms = *self.avg_latency_ms.read().await;
+
+        let messages_per_second = if uptime_seconds > 0 {
+            messages_processed as f64 / uptime_seconds as f64
+        } else {
+            0.0
+        };
+        let error_rate = if messages_processed > 0 {
+            (total_errors as f64 / messages_processed as f64) * 100.0
+        } else {
+            0.0
+        };
+
+        PerformanceMetrics {
+            total_connections,
+            peak_connections,
+            avg_latency_ms,
+            messages_per_second,
+            error_rate,
+            uptime_seconds,
+        }
+    }
+
+    /// Ping/Pongで実測したRTT(ms)を1サンプルとして取り込み、EWMA(alpha=0.2)で平滑化する。
+    /// 戻り値は(平滑化後のレイテンシ, connection_quality)。`avg_latency_ms`は
+    /// 現在生存しているクライアントのEWMAの平均として更新する。
+    pub async fn update_latency(&self, client_id: &str, rtt_ms: f64) -> (f64, String) {
+        const ALPHA: f64 = 0.2;
+
+        let smoothed = {
+            let mut ewma = self.latency_ewma.write().await;
+            let new_value = match ewma.get(client_id) {
+                Some(previous) => ALPHA * rtt_ms + (1.0 - ALPHA) * previous,
+                None => rtt_ms,
             };
+            ewma.insert(client_id.to_string(), new_value);
+            new_value
+        };
+
+        self.refresh_avg_latency().await;
+
+        let quality = Self::quality_for_latency(smoothed);
+        (smoothed, quality)
+    }
+
+    /// 平滑化後のレイテンシ(ms)から`connection_quality`を決める。50ms未満は`EXCELLENT`、
+    /// 100ms未満は`GOOD`、それ以上は`POOR`。Pong未着時の`POOR`扱いは`handle_connection`側で行う。
+    pub fn quality_for_latency(latency_ms: f64) -> String {
+        if latency_ms < 50.0 {
+            "EXCELLENT".to_string()
+        } else if latency_ms < 100.0 {
+            "GOOD".to_string()
+        } else {
+            "POOR".to_string()
         }
     }
+
+    async fn remove_latency(&self, client_id: &str) {
+        self.latency_ewma.write().await.remove(client_id);
+        self.refresh_avg_latency().await;
+    }
+
+    async fn refresh_avg_latency(&self) {
+        let ewma = self.latency_ewma.read().await;
+        let mean = if ewma.is_empty() {
+            0.0
+        } else {
+            ewma.values().sum::<f64>() / ewma.len() as f64
+        };
+        drop(ewma);
+        *self.avg_latency_ms.write().await = mean;
+    }
 }
 
 impl Default for WSServerManager {
@@ -185,26 +376,60 @@ impl Default for WSServerManager {
                 errors: 0,
                 uptime_seconds: 0,
                 started_at: None,
+                protocol: "ws".to_string(),
             })),
             clients: Arc::new(RwLock::new(HashMap::new())),
             config: Arc::new(RwLock::new(WSServerConfig {
                 port: 8080,
                 host: "127.0.0.1".to_string(),
-                auth_token: "hedge-system-default-token".to_string(),
+                server_id: "hedge-system-ws".to_string(),
+                ea_public_keys: HashMap::new(),
                 max_connections: 10,
                 heartbeat_interval_seconds: 30,
                 connection_timeout_seconds: 300,
+                shutdown_timeout_seconds: 10,
+                max_connections_per_second: 5,
+                max_messages_per_second: 50,
+                persistence: None,
+                metrics_export: None,
+                memory_warning_threshold_mb: 512,
+                tls: None,
+                disabled: false,
             })),
-            server_handle: Arc::new(Mutex::new(None)),
             started_at: Arc::new(Mutex::new(None)),
             connection_pool: Arc::new(ConnectionPool::new()),
-            heartbeat_handle: Arc::new(Mutex::new(None)),
-            performance_monitor_handle: Arc::new(Mutex::new(None)),
+            runner: BackgroundRunner::new(),
+            session_store: Arc::new(RwLock::new(None)),
+            accepting: Arc::new(AtomicBool::new(true)),
+            metrics_snapshot: Arc::new(RwLock::new(MetricsSnapshot::default())),
+            client_senders: Arc::new(RwLock::new(HashMap::new())),
+            pending_commands: Arc::new(RwLock::new(HashMap::new())),
+            system_health: SystemHealthMonitor::new(),
+            local_ipc_shutdown: Arc::new(Mutex::new(None)),
+            app_handle: Arc::new(std::sync::Mutex::new(None)),
+        }
+    }
+}
+
+/// `AppHandle`が保持されていれば`event`を全ウィンドウへemitし、なければ何もしない。
+/// `handle_connection`は`&self`を持たないため、フィールドではなく引数として渡された
+/// ハンドルをここに通す。
+fn emit_app_event<T: Serialize + Clone>(app_handle: &Arc<std::sync::Mutex<Option<AppHandle>>>, event: &str, payload: T) {
+    if let Some(app) = app_handle.lock().expect("app_handle mutex poisoned").as_ref() {
+        if let Err(e) = app.emit(event, payload) {
+            warn!("Failed to emit {}: {}", event, e);
         }
     }
 }
 
 impl WSServerManager {
+    /// Tauriの`setup`から一度だけ呼び、以降のクライアント接続/切断・サーバー状態変化イベントの
+    /// emit先として使う。`WSServerManager`自体は`Default`で`.manage()`されるため、
+    /// `AppHandle`はここで後から注入する。
+    pub fn set_app_handle(&self, app_handle: AppHandle) {
+        *self.app_handle.lock().expect("app_handle mutex poisoned") = Some(app_handle);
+    }
+
     pub async fn start_server(&self) -> Result<(), String> {
         let mut state = self.state.lock().await;
         
@@ -217,25 +442,62 @@ impl WSServerManager {
             WSServerConfig {
                 port: config_guard.port,
                 host: config_guard.host.clone(),
-                auth_token: config_guard.auth_token.clone(),
+                server_id: config_guard.server_id.clone(),
+                ea_public_keys: config_guard.ea_public_keys.clone(),
                 max_connections: config_guard.max_connections,
                 heartbeat_interval_seconds: config_guard.heartbeat_interval_seconds,
                 connection_timeout_seconds: config_guard.connection_timeout_seconds,
+                shutdown_timeout_seconds: config_guard.shutdown_timeout_seconds,
+                max_connections_per_second: config_guard.max_connections_per_second,
+                max_messages_per_second: config_guard.max_messages_per_second,
+                persistence: config_guard.persistence.clone(),
+                metrics_export: config_guard.metrics_export.clone(),
+                memory_warning_threshold_mb: config_guard.memory_warning_threshold_mb,
+                tls: config_guard.tls.clone(),
+                disabled: config_guard.disabled,
             }
         };
+
+        if config.disabled {
+            return Err("WebSocket server is disabled via config (disabled=true)".to_string());
+        }
+
+        // TLSが設定されていれば、リッスン開始前に証明書/秘密鍵を読み込み検証する
+        let tls_acceptor = match &config.tls {
+            Some(tls_config) => Some(crate::tls::build_tls_acceptor(tls_config)?),
+            None => None,
+        };
+        let protocol = if tls_acceptor.is_some() { "wss" } else { "ws" };
+
         let server_addr = format!("{}:{}", config.host, config.port);
-        
-        info!("Starting WebSocket server on {}", server_addr);
+
+        info!("Starting WebSocket server on {}://{}", protocol, server_addr);
 
         // TCPリスナーを開始
         let listener = tokio::net::TcpListener::bind(&server_addr)
             .await
             .map_err(|e| format!("Failed to bind to {}: {}", server_addr, e))?;
 
+        // 永続化が設定されていればSQLiteストアへ接続し、マイグレーションを実行する
+        if let Some(persistence_config) = &config.persistence {
+            match SessionStore::connect(persistence_config).await {
+                Ok(store) => {
+                    *self.session_store.write().await = Some(Arc::new(store));
+                    info!("Session persistence enabled at {}", persistence_config.db_path);
+                }
+                Err(e) => {
+                    return Err(format!("Failed to initialize session store: {}", e));
+                }
+            }
+        } else {
+            *self.session_store.write().await = None;
+        }
+
         // サーバー状態を更新
         state.is_running = true;
         state.port = config.port;
         state.host = config.host.clone();
+        state.protocol = protocol.to_string();
         state.started_at = Some(chrono::Utc::now().to_rfc3339());
         
         // 開始時刻を記録
@@ -243,45 +505,46 @@ impl WSServerManager {
 
         drop(state); // ロックを解放
 
-        // サーバータスクを開始
-        let server_task = self.spawn_server_task(listener, config.clone()).await;
-        *self.server_handle.lock().await = Some(server_task);
-        
-        // ハートビート監視を開始
-        let heartbeat_task = self.spawn_heartbeat_monitor(config.clone()).await;
-        *self.heartbeat_handle.lock().await = Some(heartbeat_task);
-        
-        // パフォーマンス監視を開始
-        let performance_task = self.spawn_performance_monitor().await;
-        *self.performance_monitor_handle.lock().await = Some(performance_task);
+        // 前回実行分のシャットダウンシグナルをリセットしてからランナー経由で起動
+        self.runner.reset();
+        // 前回メンテナンス中にpause_acceptingされたままだった場合に備え、起動時は必ず受付可能にする
+        self.accepting.store(true, Ordering::Relaxed);
+
+        // 受付ループ・ハートビート監視・パフォーマンス監視をランナーに登録
+        self.spawn_server_task(listener, config.clone(), tls_acceptor).await;
+        self.spawn_heartbeat_monitor(config.clone()).await;
+        self.spawn_performance_monitor(config.clone()).await;
+
+        // Prometheus pull方式が設定されている場合のみ、`/metrics`エンドポイントを立てる
+        if let Some(MetricsExportConfig::Prometheus { bind_host, bind_port }) = &config.metrics_export {
+            self.spawn_metrics_endpoint(bind_host.clone(), *bind_port).await;
+        }
+
+        info!("WebSocket server started successfully on {}://{} with high-performance features", protocol, server_addr);
+
+        emit_app_event(&self.app_handle, EVENT_SERVER_STATE_CHANGED, ServerStateChangedPayload {
+            is_running: true,
+            protocol: protocol.to_string(),
+            host: config.host.clone(),
+            port: config.port,
+            timestamp: chrono::Utc::now().to_rfc3339(),
+        });
 
-        info!("WebSocket server started successfully on {} with high-performance features", server_addr);
         Ok(())
     }
 
     pub async fn stop_server(&self) -> Result<(), String> {
         let mut state = self.state.lock().await;
-        
+
         if !state.is_running {
             return Ok(()); // 既に停止している
         }
 
         info!("Stopping WebSocket server...");
 
-        // サーバータスクを停止
-        if let Some(handle) = self.server_handle.lock().await.take() {
-            handle.abort();
-        }
-        
-        // ハートビート監視を停止
-        if let Some(handle) = self.heartbeat_handle.lock().await.take() {
-            handle.abort();
-        }
-        
-        // パフォーマンス監視を停止
-        if let Some(handle) = self.performance_monitor_handle.lock().await.take() {
-            handle.abort();
-        }
+        // 受付ループ・監視タスク・各コネクションハンドラーへシャットダウンを通知し、
+        // 待機はせず即座にクライアントを切断する（強制停止）
+        self.runner.signal_shutdown();
 
         // 全クライアントを切断
         self.disconnect_all_clients().await;
@@ -293,20 +556,149 @@ impl WSServerManager {
         *self.started_at.lock().await = None;
 
         info!("WebSocket server stopped with all monitoring systems");
+
+        emit_app_event(&self.app_handle, EVENT_SERVER_STATE_CHANGED, ServerStateChangedPayload {
+            is_running: false,
+            protocol: state.protocol.clone(),
+            host: state.host.clone(),
+            port: state.port,
+            timestamp: chrono::Utc::now().to_rfc3339(),
+        });
+
+        Ok(())
+    }
+
+    /// 既存の強制停止(`stop_server`)とは異なり、新規接続の受付を止めたうえで
+    /// 各クライアントに正常クローズフレームを送り、メッセージバッファを
+    /// フラッシュしてから`timeout_seconds`を上限に接続タスクの終了を待つ。
+    /// タイムアウトまでに終了しなかったタスクはabortされず、バックグラウンドで走り続ける。
+    pub async fn stop_server_graceful(&self, timeout_seconds: Option<u64>) -> Result<(), String> {
+        let mut state = self.state.lock().await;
+
+        if !state.is_running {
+            return Ok(()); // 既に停止している
+        }
+
+        let timeout_seconds = match timeout_seconds {
+            Some(t) => t,
+            None => self.config.read().await.shutdown_timeout_seconds,
+        };
+
+        info!("Starting graceful WebSocket server shutdown (timeout: {}s)...", timeout_seconds);
+
+        // 受付ループ・監視タスク・各コネクションハンドラーへシャットダウンを通知
+        self.runner.signal_shutdown();
+
+        // 各タスクが自発的に終了するのを待つ。コネクションはクローズフレームを
+        // 送り終えてから終了するため、タイムアウトまでは粘り強く待機する。
+        // ローカルIPCの受付ループ/コネクションは同じ`runner`レジストリに登録されてはいるが、
+        // `local_ipc_shutdown`という別のシャットダウンチャンネルで駆動されておりここでは
+        // 一切シグナルしていないため、待機対象から除外する（ローカルIPCは稼働し続けるので、
+        // 止める場合は`stop_local_ipc_server`を別途呼ぶ）。含めてしまうと、ローカルIPCが
+        // 稼働中は毎回必ずタイムアウトするまで無駄に待ち続けてしまう。
+        let deadline = Duration::from_secs(timeout_seconds);
+        let drain = async {
+            loop {
+                let all_tcp_tasks_stopped = self
+                    .runner
+                    .statuses()
+                    .await
+                    .iter()
+                    .filter(|s| !s.name.starts_with("local_ipc"))
+                    .all(|s| !s.running);
+                if all_tcp_tasks_stopped {
+                    break;
+                }
+                tokio::time::sleep(Duration::from_millis(50)).await;
+            }
+        };
+        if tokio::time::timeout(deadline, drain).await.is_err() {
+            warn!("Graceful shutdown timed out after {}s, remaining tasks will be left to finish in the background", timeout_seconds);
+        }
+
+        // 全クライアントレジストリをクリア
+        self.disconnect_all_clients().await;
+
+        state.is_running = false;
+        state.connected_clients = 0;
+        state.started_at = None;
+        *self.started_at.lock().await = None;
+
+        info!("WebSocket server stopped gracefully");
+
+        emit_app_event(&self.app_handle, EVENT_SERVER_STATE_CHANGED, ServerStateChangedPayload {
+            is_running: false,
+            protocol: state.protocol.clone(),
+            host: state.host.clone(),
+            port: state.port,
+            timestamp: chrono::Utc::now().to_rfc3339(),
+        });
+
+        Ok(())
+    }
+
+    /// TCP WebSocketリスナーとは独立に、同じJSONメッセージプロトコル(AUTH/HEARTBEAT/EAイベント)を
+    /// Unixドメインソケット(Unix)・名前付きパイプ(Windows)経由でも受け付ける。同一ホスト上で
+    /// 動くEAはTCPポートを一切使わずに接続でき、ポートスキャンの対象にもならない。
+    /// TCPサーバーが起動しているかどうかに関わらず単独で起動・停止できる。
+    pub async fn start_local_ipc_server(&self, path: String) -> Result<(), String> {
+        let mut shutdown_slot = self.local_ipc_shutdown.lock().await;
+        if shutdown_slot.is_some() {
+            return Err("Local IPC server is already running".to_string());
+        }
+
+        let listener = LocalIpcListener::bind(&path)
+            .map_err(|e| format!("Failed to bind local IPC endpoint at {}: {}", path, e))?;
+
+        let (shutdown_tx, shutdown_rx) = watch::channel(false);
+        *shutdown_slot = Some(shutdown_tx);
+        drop(shutdown_slot);
+
+        info!("Starting local IPC server at {}", path);
+        self.spawn_local_ipc_task(listener, path, shutdown_rx).await;
         Ok(())
     }
 
+    /// `start_local_ipc_server`で起動した待受を止める。既存接続はTCP側の`stop_server`と同様、
+    /// クローズフレームを待たず即座に切断される。
+    pub async fn stop_local_ipc_server(&self) -> Result<(), String> {
+        let mut shutdown_slot = self.local_ipc_shutdown.lock().await;
+        match shutdown_slot.take() {
+            Some(shutdown_tx) => {
+                let _ = shutdown_tx.send(true);
+                info!("Local IPC server stopped");
+                Ok(())
+            }
+            None => Ok(()), // 既に停止している
+        }
+    }
+
+    /// サーバー起動からの経過秒数。`Mutex<WSServerState>`は取らず`started_at`だけを読む、
+    /// `get_performance_metrics`向けの軽量なヘルパー。
+    async fn uptime_seconds(&self) -> u64 {
+        match *self.started_at.lock().await {
+            Some(started_at) => started_at.elapsed().as_secs(),
+            None => 0,
+        }
+    }
+
     pub async fn get_status(&self) -> WSServerState {
         let mut state = self.state.lock().await;
-        
+
         // 稼働時間を更新
         if let Some(started_at) = *self.started_at.lock().await {
             state.uptime_seconds = started_at.elapsed().as_secs();
         }
-        
+
         // 接続数を更新
         state.connected_clients = self.clients.read().await.len();
-        
+
+        // メッセージ/エラーカウントはホットパス上のAtomicで管理しているため、
+        // ここで参照時点の値を反映する（Mutex<WSServerState>自体には毎メッセージ書き込まない）
+        state.total_messages_received = self.connection_pool.messages_received_count();
+        state.total_messages_sent = self.connection_pool.messages_sent_count();
+        state.errors = self.connection_pool.total_errors_count();
+
         state.clone()
     }
 
@@ -316,15 +708,129 @@ impl WSServerManager {
 
     pub async fn disconnect_client(&self, client_id: &str) -> Result<(), String> {
         let mut clients = self.clients.write().await;
-        
+
         if clients.remove(client_id).is_some() {
             info!("Client {} disconnected by request", client_id);
+            emit_app_event(&self.app_handle, EVENT_CLIENT_DISCONNECTED, ClientDisconnectedPayload {
+                client_id: client_id.to_string(),
+                reason: "disconnected by operator".to_string(),
+                timestamp: chrono::Utc::now().to_rfc3339(),
+            });
             Ok(())
         } else {
             Err(format!("Client {} not found", client_id))
         }
     }
 
+    /// 特定のEAへコマンドを送り、`requestId`で相関した応答を待つ。
+    /// `RequestContainer`/`ResponseContainer`(EA→サーバー)とは逆方向の、サーバー→EAの
+    /// 往復通信を実現する。応答が来ないままタイムアウトした場合と、応答前にクライアントが
+    /// 切断した場合とで、呼び出し側が区別してリトライできるよう異なるエラーを返す。
+    pub async fn send_command(
+        &self,
+        client_id: &str,
+        command: serde_json::Value,
+        timeout_seconds: u64,
+    ) -> Result<serde_json::Value, String> {
+        let sender = {
+            let senders = self.client_senders.read().await;
+            senders
+                .get(client_id)
+                .cloned()
+                .ok_or_else(|| format!("Client {} is not connected or not yet authenticated", client_id))?
+        };
+
+        let request_id = Uuid::new_v4().to_string();
+        let envelope = serde_json::json!({
+            "type": "COMMAND",
+            "requestId": request_id,
+            "command": command,
+        }).to_string();
+
+        let (response_tx, response_rx) = oneshot::channel();
+        self.pending_commands
+            .write()
+            .await
+            .insert(request_id.clone(), (client_id.to_string(), response_tx));
+
+        if sender.send(Message::Text(envelope)).is_err() {
+            self.pending_commands.write().await.remove(&request_id);
+            return Err(format!("Client {} connection is closed", client_id));
+        }
+
+        match timeout(Duration::from_secs(timeout_seconds), response_rx).await {
+            Ok(Ok(Ok(value))) => Ok(value),
+            Ok(Ok(Err(reason))) => Err(reason),
+            Ok(Err(_)) => {
+                self.pending_commands.write().await.remove(&request_id);
+                Err("Command response channel was dropped unexpectedly".to_string())
+            }
+            Err(_) => {
+                self.pending_commands.write().await.remove(&request_id);
+                Err(format!("Command to client {} timed out after {}s", client_id, timeout_seconds))
+            }
+        }
+    }
+
+    /// 監査・再生用に記録されたイベントを、クライアント単位で時系列順に問い合わせる。
+    /// 永続化が無効な場合は空配列を返す（no-opのインメモリモード）。
+    pub async fn query_events(
+        &self,
+        client_id: &str,
+        from: Option<DateTime<Utc>>,
+        to: Option<DateTime<Utc>>,
+        msg_types: Option<Vec<String>>,
+    ) -> Result<Vec<StoredEvent>, String> {
+        let Some(store) = self.session_store.read().await.clone() else {
+            return Ok(Vec::new());
+        };
+
+        store
+            .query_events(client_id, from, to, msg_types.as_deref())
+            .await
+            .map_err(|e| format!("Failed to query events for client {}: {}", client_id, e))
+    }
+
+    /// `query_events`と同じ結果を時系列順で返す。フロントエンドはこれを`speed`倍速で
+    /// 再生(ディレイを挟んで1件ずつ適用)することでEAセッションをリプレイできる。
+    /// このコマンド自体はタイマー付きのサーバープッシュは行わず、順序付きの全イベントを返すのみ。
+    pub async fn replay_events(&self, client_id: &str) -> Result<Vec<StoredEvent>, String> {
+        self.query_events(client_id, None, None, None).await
+    }
+
+    /// 受付ループを一時停止する。既存のセッションは維持したまま新規EAの受付のみを止めるため、
+    /// メンテナンス中に接続中のEAを切断せずに済む。
+    pub fn pause_accepting(&self) {
+        self.accepting.store(false, Ordering::Relaxed);
+        info!("Accept loop paused, no new connections will be accepted until resumed");
+    }
+
+    /// `pause_accepting`で止めた受付ループを再開する
+    pub fn resume_accepting(&self) {
+        self.accepting.store(true, Ordering::Relaxed);
+        info!("Accept loop resumed, new connections will be accepted again");
+    }
+
+    pub fn is_accepting(&self) -> bool {
+        self.accepting.load(Ordering::Relaxed)
+    }
+
+    /// 認証済みの全クライアントへメッセージを送信する。`client_senders`はTCP/TLS・
+    /// ローカルIPCのどちらで接続していてもclient_id単位で同じように登録されるため、
+    /// 呼び出し側はトランスポートを意識せず両方に同時にブロードキャストできる。
+    pub async fn broadcast_message(&self, message: &str) -> Result<usize, String> {
+        let senders = self.client_senders.read().await;
+        let mut sent_count = 0;
+
+        for sender in senders.values() {
+            if sender.send(Message::Text(message.to_string())).is_ok() {
+                sent_count += 1;
+            }
+        }
+
+        Ok(sent_count)
+    }
+
     async fn disconnect_all_clients(&self) {
         let mut clients = self.clients.write().await;
         let client_count = clients.len();
@@ -335,164 +841,426 @@ impl WSServerManager {
         }
     }
 
+    // 受付ループはリスナーを一度しか束縛できないため、runnerへは再起動なしの
+    // `spawn_connection`で登録する（パニックした場合はポートの再バインドが必要になる）
     async fn spawn_server_task(
         &self,
         listener: tokio::net::TcpListener,
         config: WSServerConfig,
-    ) -> tokio::task::JoinHandle<()> {
-        let state = Arc::clone(&self.state);
+        tls_acceptor: Option<tokio_rustls::TlsAcceptor>,
+    ) {
         let clients = Arc::clone(&self.clients);
-        
-        tokio::spawn(async move {
+        let runner = self.runner.clone();
+        let mut shutdown_rx = self.runner.shutdown_receiver();
+        let session_store = self.session_store.read().await.clone();
+        let accepting = Arc::clone(&self.accepting);
+        let connection_pool = Arc::clone(&self.connection_pool);
+        let client_senders = Arc::clone(&self.client_senders);
+        let pending_commands = Arc::clone(&self.pending_commands);
+        let app_handle = Arc::clone(&self.app_handle);
+        let connection_bucket = TokenBucket::new(
+            config.max_connections_per_second.max(1),
+            config.max_connections_per_second.max(1),
+        );
+
+        self.runner.spawn_connection("accept_loop", async move {
             info!("WebSocket server listening for connections...");
-            
-            while let Ok((stream, addr)) = listener.accept().await {
-                debug!("New connection from: {}", addr);
-                
-                // 接続数制限チェック
-                let current_connections = clients.read().await.len();
-                if current_connections >= config.max_connections {
-                    warn!("Connection rejected: max connections exceeded ({})", config.max_connections);
-                    // 接続を即座にクローズ
-                    drop(stream);
-                    continue;
-                }
 
-                let state_clone = Arc::clone(&state);
-                let clients_clone = Arc::clone(&clients);
-                let config_clone = config.clone();
-                
-                // 各接続を別タスクで処理
-                tokio::spawn(async move {
-                    if let Err(e) = Self::handle_connection(
-                        stream,
-                        addr.to_string(),
-                        state_clone,
-                        clients_clone,
-                        config_clone,
-                    ).await {
-                        error!("Connection handling error: {}", e);
+            loop {
+                tokio::select! {
+                    accepted = listener.accept() => {
+                        let Ok((stream, addr)) = accepted else { break };
+                        debug!("New connection from: {}", addr);
+
+                        // メンテナンス中はpause_acceptingで新規接続の受付だけを止める
+                        if !accepting.load(Ordering::Relaxed) {
+                            debug!("Accept loop is paused, rejecting connection from {}", addr);
+                            drop(stream);
+                            continue;
+                        }
+
+                        // 接続フラッド対策のトークンバケット
+                        if !connection_bucket.try_acquire().await {
+                            warn!("Connection rejected: rate limit exceeded ({}/s) for {}", config.max_connections_per_second, addr);
+                            drop(stream);
+                            continue;
+                        }
+
+                        // 接続数制限チェック
+                        let current_connections = clients.read().await.len();
+                        if current_connections >= config.max_connections {
+                            warn!("Connection rejected: max connections exceeded ({})", config.max_connections);
+                            // 接続を即座にクローズ
+                            drop(stream);
+                            continue;
+                        }
+
+                        let clients_clone = Arc::clone(&clients);
+                        let config_clone = config.clone();
+                        let connection_shutdown_rx = shutdown_rx.clone();
+                        let session_store_clone = session_store.clone();
+                        let connection_pool_clone = Arc::clone(&connection_pool);
+                        let client_senders_clone = Arc::clone(&client_senders);
+                        let pending_commands_clone = Arc::clone(&pending_commands);
+                        let tls_acceptor_clone = tls_acceptor.clone();
+                        let app_handle_clone = Arc::clone(&app_handle);
+
+                        // 各接続をランナー経由で処理（パニックしても再起動はしない）
+                        runner.spawn_connection(format!("conn:{}", addr), async move {
+                            // TLSが設定されている場合はWebSocketハンドシェイクの前に終端する
+                            let stream = match tls_acceptor_clone {
+                                Some(acceptor) => match acceptor.accept(stream).await {
+                                    Ok(tls_stream) => MaybeTlsStream::Tls(Box::new(tls_stream)),
+                                    Err(e) => {
+                                        warn!("TLS handshake failed for {}: {}", addr, e);
+                                        return;
+                                    }
+                                },
+                                None => MaybeTlsStream::Plain(stream),
+                            };
+
+                            if let Err(e) = Self::handle_connection(
+                                stream,
+                                addr.to_string(),
+                                "tcp",
+                                clients_clone,
+                                config_clone,
+                                connection_shutdown_rx,
+                                session_store_clone,
+                                connection_pool_clone,
+                                client_senders_clone,
+                                pending_commands_clone,
+                                app_handle_clone,
+                            ).await {
+                                error!("Connection handling error: {}", e);
+                            }
+                        });
                     }
-                });
+                    _ = shutdown_rx.changed() => {
+                        info!("Accept loop received shutdown signal, no longer accepting new connections");
+                        break;
+                    }
+                }
             }
-        })
+        });
     }
 
-    // 高性能ハートビート監視システム
-    async fn spawn_heartbeat_monitor(
+    /// ローカルIPC(Unixドメインソケット/名前付きパイプ)の受付ループ。接続フラッド対策の
+    /// トークンバケットは対象外（同一ホストのプロセスのみが到達できるため）だが、
+    /// 同時接続数の上限は`max_connections`をそのままTCP側と共有する。
+    async fn spawn_local_ipc_task(
         &self,
-        config: WSServerConfig,
-    ) -> tokio::task::JoinHandle<()> {
+        mut listener: LocalIpcListener,
+        path: String,
+        mut shutdown_rx: watch::Receiver<bool>,
+    ) {
         let clients = Arc::clone(&self.clients);
+        let runner = self.runner.clone();
+        let session_store = self.session_store.read().await.clone();
         let connection_pool = Arc::clone(&self.connection_pool);
-        
-        tokio::spawn(async move {
-            let mut heartbeat_interval = interval(Duration::from_secs(config.heartbeat_interval_seconds));
-            
-            info!("Heartbeat monitor started with {}s interval", config.heartbeat_interval_seconds);
-            
+        let client_senders = Arc::clone(&self.client_senders);
+        let pending_commands = Arc::clone(&self.pending_commands);
+        let app_handle = Arc::clone(&self.app_handle);
+        let config = self.config.read().await.clone();
+
+        self.runner.spawn_connection("local_ipc_accept_loop", async move {
+            info!("Local IPC server listening at {}", path);
+
+            // TCP側の`conn:{addr}`と同様、同時接続ごとに一意な名前で登録する。固定の
+            // "local_ipc_conn"のままだと複数接続時に`spawn_connection`の終了処理が
+            // 別コネクションのレジストリエントリを巻き込んで消してしまう
+            let next_conn_id = AtomicU64::new(0);
+
             loop {
-                heartbeat_interval.tick().await;
-                
-                // クライアントの生存確認
-                let now = chrono::Utc::now();
-                let timeout_threshold = Duration::from_secs(config.connection_timeout_seconds);
-                let mut inactive_clients = Vec::new();
-                
-                {
-                    let clients_lock = clients.read().await;
-                    for (client_id, client) in clients_lock.iter() {
-                        if let Ok(last_heartbeat) = chrono::DateTime::parse_from_rfc3339(&client.last_heartbeat) {
-                            let elapsed = now.signed_duration_since(last_heartbeat);
-                            if elapsed.num_seconds() as u64 > config.connection_timeout_seconds {
-                                inactive_clients.push(client_id.clone());
+                tokio::select! {
+                    accepted = listener.accept() => {
+                        let stream = match accepted {
+                            Ok(stream) => stream,
+                            Err(e) => {
+                                error!("Failed to accept local IPC connection at {}: {}", path, e);
+                                continue;
                             }
+                        };
+
+                        let current_connections = clients.read().await.len();
+                        if current_connections >= config.max_connections {
+                            warn!("Local IPC connection rejected: max connections exceeded ({})", config.max_connections);
+                            drop(stream);
+                            continue;
                         }
+
+                        let clients_clone = Arc::clone(&clients);
+                        let config_clone = config.clone();
+                        let connection_shutdown_rx = shutdown_rx.clone();
+                        let session_store_clone = session_store.clone();
+                        let connection_pool_clone = Arc::clone(&connection_pool);
+                        let client_senders_clone = Arc::clone(&client_senders);
+                        let pending_commands_clone = Arc::clone(&pending_commands);
+                        let app_handle_clone = Arc::clone(&app_handle);
+
+                        let conn_id = next_conn_id.fetch_add(1, Ordering::Relaxed);
+                        runner.spawn_connection(format!("local_ipc_conn:{}", conn_id), async move {
+                            if let Err(e) = Self::handle_connection(
+                                stream,
+                                "local_ipc".to_string(),
+                                "local_ipc",
+                                clients_clone,
+                                config_clone,
+                                connection_shutdown_rx,
+                                session_store_clone,
+                                connection_pool_clone,
+                                client_senders_clone,
+                                pending_commands_clone,
+                                app_handle_clone,
+                            ).await {
+                                error!("Local IPC connection handling error: {}", e);
+                            }
+                        });
                     }
-                }
-                
-                // 非アクティブクライアントを削除
-                if !inactive_clients.is_empty() {
-                    let mut clients_lock = clients.write().await;
-                    for client_id in inactive_clients {
-                        warn!("Removing inactive client: {}", client_id);
-                        clients_lock.remove(&client_id);
-                        connection_pool.remove_connection(&client_id).await;
+                    _ = shutdown_rx.changed() => {
+                        info!("Local IPC accept loop received shutdown signal, no longer accepting new connections");
+                        break;
                     }
                 }
-                
-                // ハートビートメッセージを送信
-                let heartbeat_msg = serde_json::json!({
-                    "type": "HEARTBEAT",
-                    "timestamp": now.to_rfc3339(),
-                    "server": "hedge-system-ws"
-                }).to_string();
-                
-                if let Ok(sent_count) = connection_pool.broadcast_message(&heartbeat_msg).await {
-                    debug!("Heartbeat sent to {} clients", sent_count);
-                }
             }
-        })
+        });
     }
 
-    // パフォーマンス監視システム
-    async fn spawn_performance_monitor(&self) -> tokio::task::JoinHandle<()> {
+    // 高性能ハートビート監視システム
+    async fn spawn_heartbeat_monitor(&self, config: WSServerConfig) {
+        let clients = Arc::clone(&self.clients);
         let connection_pool = Arc::clone(&self.connection_pool);
-        let state = Arc::clone(&self.state);
-        let started_at = Arc::clone(&self.started_at);
-        
-        tokio::spawn(async move {
-            let mut monitor_interval = interval(Duration::from_secs(10)); // 10秒間隔
-            
-            info!("Performance monitor started");
-            
-            loop {
-                monitor_interval.tick().await;
-                
-                // パフォーマンスメトリクスを更新
-                let metrics = connection_pool.get_performance_metrics().await;
-                
-                // サーバー状態を更新
-                if let Ok(mut state_lock) = state.try_lock() {
-                    if let Some(start_time) = *started_at.lock().await {
-                        state_lock.uptime_seconds = start_time.elapsed().as_secs();
-                    }
-                    
-                    // メッセージ/秒を計算
-                    if state_lock.uptime_seconds > 0 {
-                        let msg_per_sec = state_lock.total_messages_received as f64 / state_lock.uptime_seconds as f64;
-                        
-                        // パフォーマンス警告
-                        if msg_per_sec > 1000.0 {
-                            warn!("High message rate detected: {:.2} msg/s", msg_per_sec);
-                        }
-                        
-                        if metrics.avg_latency_ms > 100.0 {
-                            warn!("High latency detected: {:.2}ms", metrics.avg_latency_ms);
+        let session_store = Arc::clone(&self.session_store);
+        let client_senders = Arc::clone(&self.client_senders);
+
+        self.runner.spawn("heartbeat", move || {
+            let clients = Arc::clone(&clients);
+            let connection_pool = Arc::clone(&connection_pool);
+            let session_store = Arc::clone(&session_store);
+            let client_senders = Arc::clone(&client_senders);
+            let config = config.clone();
+
+            async move {
+                let mut heartbeat_interval = interval(Duration::from_secs(config.heartbeat_interval_seconds));
+
+                info!("Heartbeat monitor started with {}s interval", config.heartbeat_interval_seconds);
+
+                loop {
+                    heartbeat_interval.tick().await;
+
+                    // クライアントの生存確認
+                    let now = chrono::Utc::now();
+                    let mut inactive_clients = Vec::new();
+
+                    {
+                        let clients_lock = clients.read().await;
+                        for (client_id, client) in clients_lock.iter() {
+                            if let Ok(last_heartbeat) = chrono::DateTime::parse_from_rfc3339(&client.last_heartbeat) {
+                                let elapsed = now.signed_duration_since(last_heartbeat);
+                                if elapsed.num_seconds() as u64 > config.connection_timeout_seconds {
+                                    let account = client.ea_info.as_ref().map(|i| i.account.clone());
+                                    inactive_clients.push((client_id.clone(), account));
+                                }
+                            }
                         }
-                        
-                        if metrics.error_rate > 5.0 {
-                            warn!("High error rate detected: {:.2}%", metrics.error_rate);
+                    }
+
+                    // 非アクティブクライアントを削除
+                    if !inactive_clients.is_empty() {
+                        let mut clients_lock = clients.write().await;
+                        for (client_id, account) in inactive_clients {
+                            warn!("Removing inactive client: {}", client_id);
+                            clients_lock.remove(&client_id);
+                            connection_pool.remove_connection(&client_id).await;
+
+                            // 永続化が有効なら、ハートビート欠落による強制切断を監査ログに残す
+                            if let Some(store) = session_store.read().await.clone() {
+                                if let Err(e) = store
+                                    .record_connection_event(&client_id, account.as_deref(), "HEARTBEAT_GAP")
+                                    .await
+                                {
+                                    error!("Failed to record HEARTBEAT_GAP connection event for {}: {}", client_id, e);
+                                }
+                            }
                         }
                     }
+
+                    // ハートビートメッセージを送信
+                    let heartbeat_msg = serde_json::json!({
+                        "type": "HEARTBEAT",
+                        "timestamp": now.to_rfc3339(),
+                        "server": "hedge-system-ws"
+                    }).to_string();
+
+                    let senders = client_senders.read().await;
+                    let sent_count = senders
+                        .values()
+                        .filter(|sender| sender.send(Message::Text(heartbeat_msg.clone())).is_ok())
+                        .count();
+                    drop(senders);
+                    debug!("Heartbeat sent to {} clients", sent_count);
                 }
-                
-                debug!("Performance metrics - Connections: {}, Peak: {}, Avg latency: {:.2}ms", 
-                       metrics.total_connections, metrics.peak_connections, metrics.avg_latency_ms);
             }
-        })
+        });
     }
 
-    async fn handle_connection(
-        stream: tokio::net::TcpStream,
+    // パフォーマンス監視システム
+    async fn spawn_performance_monitor(&self, config: WSServerConfig) {
+        let connection_pool = Arc::clone(&self.connection_pool);
+        let state = Arc::clone(&self.state);
+        let started_at = Arc::clone(&self.started_at);
+        let session_store = Arc::clone(&self.session_store);
+        let clients = Arc::clone(&self.clients);
+        let metrics_snapshot = Arc::clone(&self.metrics_snapshot);
+
+        self.runner.spawn("performance_monitor", move || {
+            let connection_pool = Arc::clone(&connection_pool);
+            let state = Arc::clone(&state);
+            let started_at = Arc::clone(&started_at);
+            let session_store = Arc::clone(&session_store);
+            let clients = Arc::clone(&clients);
+            let metrics_snapshot = Arc::clone(&metrics_snapshot);
+            let config = config.clone();
+
+            async move {
+                let mut monitor_interval = interval(Duration::from_secs(10)); // 10秒間隔
+                // retention_daysを超えた古いセッション/スナップショット行の掃除は毎ティックではなく
+                // 1日に1回で十分なため、前回実行時刻をこのタスクのローカル状態として持つ
+                const PRUNE_INTERVAL: Duration = Duration::from_secs(24 * 60 * 60);
+                let mut last_pruned_at: Option<Instant> = None;
+
+                info!("Performance monitor started");
+
+                loop {
+                    monitor_interval.tick().await;
+
+                    // ホットパスのロックを避けるため、uptimeは`started_at`から直接算出する
+                    let uptime_seconds = match *started_at.lock().await {
+                        Some(start_time) => start_time.elapsed().as_secs(),
+                        None => 0,
+                    };
+
+                    // パフォーマンスメトリクスを更新
+                    let metrics = connection_pool.get_performance_metrics(uptime_seconds).await;
+
+                    // サーバー状態を更新
+                    if let Ok(mut state_lock) = state.try_lock() {
+                        state_lock.uptime_seconds = uptime_seconds;
+
+                        if uptime_seconds > 0 {
+                            // パフォーマンス警告
+                            if metrics.messages_per_second > 1000.0 {
+                                warn!("High message rate detected: {:.2} msg/s", metrics.messages_per_second);
+                            }
+
+                            if metrics.avg_latency_ms > 100.0 {
+                                warn!("High latency detected: {:.2}ms", metrics.avg_latency_ms);
+                            }
+
+                            if metrics.error_rate > 5.0 {
+                                warn!("High error rate detected: {:.2}%", metrics.error_rate);
+                            }
+                        }
+                    }
+
+                    debug!("Performance metrics - Connections: {}, Peak: {}, Avg latency: {:.2}ms",
+                           metrics.total_connections, metrics.peak_connections, metrics.avg_latency_ms);
+
+                    // メトリクスエクスポート用のスナップショットを更新する（Prometheus/InfluxDB共通）
+                    if config.metrics_export.is_some() {
+                        let total_messages_received = connection_pool.messages_received_count();
+                        let total_messages_sent = connection_pool.messages_sent_count();
+                        let messages_per_second = metrics.messages_per_second;
+                        let clients_lock = clients.read().await;
+                        let client_latencies_ms = clients_lock
+                            .values()
+                            .filter_map(|c| c.latency_ms.map(|l| (c.id.clone(), l)))
+                            .collect();
+                        let connected_clients = clients_lock.len();
+                        drop(clients_lock);
+
+                        let snapshot = MetricsSnapshot {
+                            connected_clients,
+                            peak_connections: metrics.peak_connections,
+                            avg_latency_ms: metrics.avg_latency_ms,
+                            messages_per_second,
+                            error_rate: metrics.error_rate,
+                            total_messages_received,
+                            total_messages_sent,
+                            client_latencies_ms,
+                        };
+
+                        *metrics_snapshot.write().await = snapshot.clone();
+
+                        if let Some(MetricsExportConfig::InfluxDb { url, bucket, token }) = &config.metrics_export {
+                            if let Err(e) = crate::metrics::push_influx(url, bucket, token, &snapshot).await {
+                                error!("Failed to push metrics to InfluxDB: {}", e);
+                            }
+                        }
+                    }
+
+                    // 永続化が有効なら、このティックのメトリクスをスナップショットとして保存する
+                    if let Some(store) = session_store.read().await.clone() {
+                        if let Err(e) = store.record_performance_snapshot(
+                            metrics.total_connections,
+                            metrics.peak_connections,
+                            metrics.avg_latency_ms,
+                            metrics.error_rate,
+                        ).await {
+                            error!("Failed to record performance snapshot: {}", e);
+                        }
+
+                        // retention_daysを過ぎた古い行をこのタスクから定期的に掃除する
+                        if last_pruned_at.map_or(true, |t| t.elapsed() >= PRUNE_INTERVAL) {
+                            let retention_days = config
+                                .persistence
+                                .as_ref()
+                                .map(|p| p.retention_days)
+                                .unwrap_or(30);
+                            store.prune_expired(retention_days).await;
+                            last_pruned_at = Some(Instant::now());
+                        }
+                    }
+                }
+            }
+        });
+    }
+
+    /// Prometheus pull方式の`/metrics`を立てる。最新スナップショットは
+    /// パフォーマンス監視ティック(`spawn_performance_monitor`)が10秒間隔で更新する。
+    async fn spawn_metrics_endpoint(&self, bind_host: String, bind_port: u16) {
+        let metrics_snapshot = Arc::clone(&self.metrics_snapshot);
+        let shutdown_rx = self.runner.shutdown_receiver();
+
+        self.runner.spawn_connection("metrics_endpoint", async move {
+            crate::metrics::serve_prometheus(bind_host, bind_port, metrics_snapshot, shutdown_rx).await;
+        });
+    }
+
+    /// TCP/TLS(`MaybeTlsStream`)・ローカルIPC(`LocalIpcStream`)のどちらから呼ばれても、
+    /// `accept_async`以降の認証・メッセージ処理はまったく同じロジックを通る。
+    /// `tokio_tungstenite::accept_async`自体が`AsyncRead + AsyncWrite + Unpin`であれば
+    /// 型を問わないため、ここをジェネリックにするだけで双方のトランスポートに対応できる。
+    async fn handle_connection<S>(
+        stream: S,
         client_addr: String,
-        state: Arc<Mutex<WSServerState>>,
+        transport: &'static str,
         clients: Arc<RwLock<HashMap<String, ClientConnection>>>,
         config: WSServerConfig,
-    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        mut shutdown_rx: watch::Receiver<bool>,
+        session_store: Option<Arc<SessionStore>>,
+        connection_pool: Arc<ConnectionPool>,
+        client_senders: Arc<RwLock<HashMap<String, mpsc::UnboundedSender<Message>>>>,
+        pending_commands: Arc<RwLock<HashMap<String, (String, oneshot::Sender<Result<serde_json::Value, String>>)>>>,
+        app_handle: Arc<std::sync::Mutex<Option<AppHandle>>>,
+    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>>
+    where
+        S: AsyncRead + AsyncWrite + Unpin,
+    {
         let ws_stream = accept_async(stream).await?;
         let (mut ws_sender, mut ws_receiver) = ws_stream.split();
-        
+        let (outbound_tx, mut outbound_rx) = mpsc::unbounded_channel::<Message>();
+
         let client_id = Uuid::new_v4().to_string();
         let now = chrono::Utc::now().to_rfc3339();
         
@@ -509,24 +1277,194 @@ impl WSServerManager {
             connection_quality: "UNKNOWN".to_string(),
             message_buffer_size: 0,
             last_message_timestamp: now,
+            pubkey: None,
+            transport: transport.to_string(),
         };
         
         clients.write().await.insert(client_id.clone(), client);
-        
+
         info!("Client {} connected from {}", client_id, client_addr);
+        emit_app_event(&app_handle, EVENT_CLIENT_CONNECTED, ClientConnectedPayload {
+            client_id: client_id.clone(),
+            remote_addr: client_addr.clone(),
+            transport: transport.to_string(),
+            timestamp: chrono::Utc::now().to_rfc3339(),
+        });
 
-        // メッセージ処理ループ（高性能版）
-        while let Some(msg) = ws_receiver.next().await {
+        // ed25519チャレンジ-レスポンスによるハンドシェイク。失敗したら切断する
+        match Self::perform_auth_handshake(&mut ws_sender, &mut ws_receiver, &config).await {
+            Ok(pubkey) => {
+                let mut clients_lock = clients.write().await;
+                if let Some(client) = clients_lock.get_mut(&client_id) {
+                    client.authenticated = true;
+                    client.pubkey = Some(pubkey);
+                }
+                drop(clients_lock);
+                // 認証済みクライアントのみ、send_commandからのユニキャスト送信先になれる
+                client_senders.write().await.insert(client_id.clone(), outbound_tx.clone());
+                info!("Client {} authenticated via ed25519 handshake", client_id);
+
+                // 永続化が有効なら、監査ログに認証成功イベントを記録する(account未登録ならNone)
+                if let Some(store) = &session_store {
+                    if let Err(e) = store.record_connection_event(&client_id, None, "AUTH").await {
+                        error!("Failed to record AUTH connection event for {}: {}", client_id, e);
+                    }
+                }
+            }
+            Err(reason) => {
+                warn!("Client {} failed auth handshake: {}", client_id, reason);
+                let disconnect_reason = format!("auth handshake failed: {}", reason);
+                let error_response = serde_json::json!({
+                    "type": "ERROR",
+                    "code": "AUTH_FAILED",
+                    "message": reason,
+                }).to_string();
+                let _ = ws_sender.send(Message::Text(error_response)).await;
+                let _ = ws_sender.send(Message::Close(None)).await;
+                clients.write().await.remove(&client_id);
+                emit_app_event(&app_handle, EVENT_CLIENT_DISCONNECTED, ClientDisconnectedPayload {
+                    client_id: client_id.clone(),
+                    reason: disconnect_reason,
+                    timestamp: chrono::Utc::now().to_rfc3339(),
+                });
+                return Ok(());
+            }
+        }
+
+        // クライアント単位のメッセージレート制限。メッセージ連打(ストーム)を行う
+        // 悪質なクライアントを検出し、一定回数を超えたら強制切断する。
+        let message_bucket = TokenBucket::new(
+            config.max_messages_per_second.max(1),
+            config.max_messages_per_second.max(1),
+        );
+        let mut rate_limit_violations: u32 = 0;
+
+        // RTT計測用のPingプローバー。接続開始時刻を起点としたモノトニックな経過ナノ秒を
+        // Pingのペイロードに積み、対応するPongが返ってきた時点でRTTを逆算する。
+        let conn_start = Instant::now();
+        let mut ping_interval = interval(Duration::from_secs(config.heartbeat_interval_seconds));
+        let mut ping_outstanding = false;
+
+        // `client-disconnected`イベントに添える切断理由。各breakの直前で上書きし、
+        // どこにも当てはまらなかった場合のデフォルトは「接続が閉じられた」とする。
+        let mut disconnect_reason = "connection closed".to_string();
+
+        // メッセージ処理ループ（高性能版、グレースフルシャットダウン対応）
+        loop {
+            let msg = tokio::select! {
+                msg = ws_receiver.next() => match msg {
+                    Some(msg) => msg,
+                    None => break,
+                },
+                _ = ping_interval.tick() => {
+                    if ping_outstanding {
+                        // 前回のPingにPongが返ってこないまま次のティックを迎えた＝タイムアウト。
+                        // last_heartbeatは更新せず、非アクティブ監視(ハートビートモニター)の
+                        // タイムアウト判定に自然に積み上がらせる。
+                        warn!("Client {} missed a Pong within the ping interval, marking quality POOR", client_id);
+                        let mut clients_lock = clients.write().await;
+                        if let Some(client) = clients_lock.get_mut(&client_id) {
+                            client.connection_quality = "POOR".to_string();
+                        }
+                    }
+
+                    let sent_ts_nanos = conn_start.elapsed().as_nanos() as u64;
+                    let payload = sent_ts_nanos.to_be_bytes().to_vec();
+                    if let Err(e) = ws_sender.send(Message::Ping(payload)).await {
+                        error!("Failed to send latency-probe ping to {}: {}", client_id, e);
+                        disconnect_reason = "ping send failed".to_string();
+                        break;
+                    }
+                    ping_outstanding = true;
+                    continue;
+                }
+                Some(outbound_msg) = outbound_rx.recv() => {
+                    if let Err(e) = ws_sender.send(outbound_msg).await {
+                        error!("Failed to deliver outbound command to {}: {}", client_id, e);
+                        break;
+                    }
+                    continue;
+                }
+                _ = shutdown_rx.changed() => {
+                    if *shutdown_rx.borrow() {
+                        info!("Draining client {} for graceful shutdown", client_id);
+                        disconnect_reason = "server shutting down".to_string();
+
+                        // persistenceの有無に関わらず、まだ送れていないoutbound_rx上の
+                        // メッセージをCloseフレームより先に配信しきる
+                        let mut flushed_count = 0;
+                        while let Ok(outbound_msg) = outbound_rx.try_recv() {
+                            if let Err(e) = ws_sender.send(outbound_msg).await {
+                                error!("Failed to flush pending outbound message to {} during shutdown: {}", client_id, e);
+                                break;
+                            }
+                            flushed_count += 1;
+                        }
+                        if flushed_count > 0 {
+                            info!("Flushed {} pending outbound message(s) to {} before shutdown", flushed_count, client_id);
+                        }
+
+                        let close_frame = CloseFrame {
+                            code: CloseCode::Normal,
+                            reason: "server shutting down".into(),
+                        };
+                        if let Err(e) = ws_sender.send(Message::Close(Some(close_frame))).await {
+                            error!("Failed to send close frame to {}: {}", client_id, e);
+                        }
+                        let _ = ws_sender.flush().await;
+                    }
+                    break;
+                }
+            };
             let message_start_time = Instant::now();
-            
+
             match msg {
                 Ok(Message::Text(text)) => {
-                    // メッセージ統計を更新
-                    {
-                        let mut state_lock = state.lock().await;
-                        state_lock.total_messages_received += 1;
+                    // send_commandが発行した保留中コマンドへの応答であれば、通常のリクエスト
+                    // ディスパッチを経由せずoneshotへ直接ルーティングする
+                    if let Ok(raw) = serde_json::from_str::<serde_json::Value>(&text) {
+                        if let Some(request_id) = raw.get("requestId").and_then(|v| v.as_str()) {
+                            let pending_entry = pending_commands.write().await.remove(request_id);
+                            if let Some((_, response_tx)) = pending_entry {
+                                debug!("Routed response for pending command {} from {}", request_id, client_id);
+                                let _ = response_tx.send(Ok(raw));
+                                continue;
+                            }
+                        }
                     }
-                    
+
+                    // メッセージストーム対策のレート制限
+                    if !message_bucket.try_acquire().await {
+                        rate_limit_violations += 1;
+                        warn!(
+                            "Client {} exceeded message rate limit ({}/s), violation {}/{}",
+                            client_id, config.max_messages_per_second, rate_limit_violations, MAX_MESSAGE_RATE_VIOLATIONS
+                        );
+
+                        {
+                            let mut clients_lock = clients.write().await;
+                            if let Some(client) = clients_lock.get_mut(&client_id) {
+                                client.error_count += 1;
+                            }
+                        }
+
+                        if rate_limit_violations >= MAX_MESSAGE_RATE_VIOLATIONS {
+                            warn!("Closing client {} for repeated message rate violations", client_id);
+                            disconnect_reason = "message rate limit exceeded".to_string();
+                            let close_frame = CloseFrame {
+                                code: CloseCode::Policy,
+                                reason: "message rate limit exceeded".into(),
+                            };
+                            let _ = ws_sender.send(Message::Close(Some(close_frame))).await;
+                            break;
+                        }
+
+                        continue;
+                    }
+
+                    // メッセージ統計を更新（Mutex<WSServerState>は取らず、Atomicへ加算するのみ）
+                    connection_pool.record_message_received();
+
                     // クライアント統計を更新（高性能機能付き）
                     {
                         let mut clients_lock = clients.write().await;
@@ -540,52 +1478,38 @@ impl WSServerManager {
                     debug!("Received message from {}: {}", client_id, text);
                     
                     // レイテンシ測定付きメッセージ処理
-                    match Self::process_message(&text, &client_id, &clients, &config).await {
+                    match Self::process_message(&text, &client_id, &clients, &config, &session_store, &client_senders).await {
                         Ok(response) => {
                             if let Some(resp) = response {
-                                let send_start = Instant::now();
-                                
                                 if let Err(e) = ws_sender.send(Message::Text(resp)).await {
                                     error!("Failed to send response to {}: {}", client_id, e);
                                     break;
                                 }
                                 
-                                // レイテンシ計算・記録
-                                let total_latency = message_start_time.elapsed().as_millis() as f64;
-                                
-                                // クライアント情報を更新
-                                {
-                                    let mut clients_lock = clients.write().await;
-                                    if let Some(client) = clients_lock.get_mut(&client_id) {
-                                        client.latency_ms = Some(total_latency);
-                                        client.connection_quality = if total_latency < 50.0 {
-                                            "EXCELLENT".to_string()
-                                        } else if total_latency < 100.0 {
-                                            "GOOD".to_string()
-                                        } else {
-                                            "POOR".to_string()
-                                        };
-                                    }
-                                }
-                                
-                                let mut state_lock = state.lock().await;
-                                state_lock.total_messages_sent += 1;
-                                
-                                // パフォーマンス警告
-                                if total_latency > 100.0 {
-                                    warn!("High latency detected for client {}: {:.2}ms", client_id, total_latency);
+                                // サーバー側の処理時間（ネットワークRTTではない）。`latency_ms`/
+                                // `connection_quality`はPing/Pongで実測したRTTのEWMAで管理するため、
+                                // ここでは遅い処理の検知にのみ使う。
+                                let processing_time_ms = message_start_time.elapsed().as_millis() as f64;
+
+                                connection_pool.record_message_sent();
+
+                                if processing_time_ms > 100.0 {
+                                    warn!("Slow message processing for client {}: {:.2}ms", client_id, processing_time_ms);
                                 }
                             }
                         }
-                        Err(e) => {
-                            error!("Message processing error for {}: {}", client_id, e);
-                            
-                            // エラー統計を更新
-                            {
-                                let mut state_lock = state.lock().await;
-                                state_lock.errors += 1;
+                        Err(error_response_json) => {
+                            error!("Message processing error for {}: {}", client_id, error_response_json);
+
+                            // 機械可読なErrorResponseをクライアントへ送り返す
+                            if let Err(e) = ws_sender.send(Message::Text(error_response_json)).await {
+                                error!("Failed to send error response to {}: {}", client_id, e);
+                                break;
                             }
-                            
+
+                            // エラー統計を更新（Mutex<WSServerState>は取らず、Atomicへ加算するのみ）
+                            connection_pool.record_error();
+
                             {
                                 let mut clients_lock = clients.write().await;
                                 if let Some(client) = clients_lock.get_mut(&client_id) {
@@ -605,16 +1529,31 @@ impl WSServerManager {
                         break;
                     }
                 }
-                Ok(Message::Pong(_)) => {
+                Ok(Message::Pong(payload)) => {
                     debug!("Pong from {}", client_id);
-                    // heartbeatを更新
-                    let mut clients_lock = clients.write().await;
-                    if let Some(client) = clients_lock.get_mut(&client_id) {
-                        client.last_heartbeat = chrono::Utc::now().to_rfc3339();
+                    ping_outstanding = false;
+
+                    // ペイロードはPing送信時に積んだ「接続開始からの経過ナノ秒」。
+                    // 現在の経過時間との差分が真のRTT。
+                    if let Ok(sent_ts_nanos) = payload.as_slice().try_into().map(u64::from_be_bytes) {
+                        let now_nanos = conn_start.elapsed().as_nanos() as u64;
+                        let rtt_ms = now_nanos.saturating_sub(sent_ts_nanos) as f64 / 1_000_000.0;
+
+                        let (smoothed, quality) = connection_pool.update_latency(&client_id, rtt_ms).await;
+
+                        let mut clients_lock = clients.write().await;
+                        if let Some(client) = clients_lock.get_mut(&client_id) {
+                            client.latency_ms = Some(smoothed);
+                            client.connection_quality = quality;
+                            client.last_heartbeat = chrono::Utc::now().to_rfc3339();
+                        }
+                    } else {
+                        warn!("Received Pong from {} with an unexpected payload size", client_id);
                     }
                 }
                 Ok(Message::Close(_)) => {
                     info!("Client {} disconnected", client_id);
+                    disconnect_reason = "closed by client".to_string();
                     break;
                 }
                 Ok(Message::Frame(_)) => {
@@ -623,98 +1562,292 @@ impl WSServerManager {
                 }
                 Err(e) => {
                     error!("WebSocket error for {}: {}", client_id, e);
+                    disconnect_reason = format!("websocket error: {}", e);
                     break;
                 }
             }
         }
 
         // クライアントを削除
-        clients.write().await.remove(&client_id);
+        let removed_client = clients.write().await.remove(&client_id);
+        connection_pool.remove_connection(&client_id).await;
+        client_senders.write().await.remove(&client_id);
         info!("Client {} removed", client_id);
-        
+        emit_app_event(&app_handle, EVENT_CLIENT_DISCONNECTED, ClientDisconnectedPayload {
+            client_id: client_id.clone(),
+            reason: disconnect_reason,
+            timestamp: chrono::Utc::now().to_rfc3339(),
+        });
+
+        // このクライアント宛てに応答を待っていたsend_command呼び出しがあれば、タイムアウトと
+        // 区別できるよう明示的に"切断された"エラーで解決する
+        {
+            let mut pending = pending_commands.write().await;
+            let stale_request_ids: Vec<String> = pending
+                .iter()
+                .filter(|(_, (cid, _))| cid == &client_id)
+                .map(|(request_id, _)| request_id.clone())
+                .collect();
+            for request_id in stale_request_ids {
+                if let Some((_, response_tx)) = pending.remove(&request_id) {
+                    let _ = response_tx.send(Err(format!("Client {} disconnected before responding", client_id)));
+                }
+            }
+        }
+
+        // 永続化が有効なら切断時刻を記録し、resume_window内の再接続で復元できるようにする
+        let disconnected_account = removed_client.and_then(|c| c.ea_info).map(|i| i.account);
+        if let Some(store) = &session_store {
+            if let Some(account) = &disconnected_account {
+                if let Err(e) = store.mark_disconnected(account).await {
+                    error!("Failed to record disconnection for account {}: {}", account, e);
+                }
+
+                // まだ送り切れていなかったコマンドをアカウント単位でバッファしておき、
+                // resume_window内に再接続してきた際に`find_resumable_session`経由で
+                // 拾えるようにする
+                let mut buffered_count = 0;
+                while let Ok(pending_msg) = outbound_rx.try_recv() {
+                    if let Message::Text(text) = pending_msg {
+                        if let Err(e) = store.buffer_pending_message(account, &text).await {
+                            error!("Failed to buffer pending message for account {}: {}", account, e);
+                        } else {
+                            buffered_count += 1;
+                        }
+                    }
+                }
+                if buffered_count > 0 {
+                    info!("Buffered {} undelivered message(s) for account {}", buffered_count, account);
+                }
+            }
+
+            if let Err(e) = store
+                .record_connection_event(&client_id, disconnected_account.as_deref(), "DISCONNECT")
+                .await
+            {
+                error!("Failed to record DISCONNECT connection event for {}: {}", client_id, e);
+            }
+        }
+
         Ok(())
     }
 
+    /// ed25519チャレンジ-レスポンスハンドシェイク。
+    /// サーバーが32バイトの乱数nonceを送り、EAは`sign(nonce || server_id)`を返す。
+    /// 署名がアロウリスト内の公開鍵で検証できた場合のみ、その公開鍵(base64)を返す。
+    async fn perform_auth_handshake<S, R>(
+        ws_sender: &mut S,
+        ws_receiver: &mut R,
+        config: &WSServerConfig,
+    ) -> Result<String, String>
+    where
+        S: futures_util::Sink<Message> + Unpin,
+        S::Error: std::fmt::Display,
+        R: futures_util::Stream<Item = Result<Message, tokio_tungstenite::tungstenite::Error>> + Unpin,
+    {
+        let mut nonce = [0u8; 32];
+        rand::rngs::OsRng.fill_bytes(&mut nonce);
+        let nonce_b64 = BASE64.encode(nonce);
+
+        let challenge = serde_json::json!({
+            "type": "AUTH_CHALLENGE",
+            "nonce": nonce_b64,
+        }).to_string();
+
+        ws_sender
+            .send(Message::Text(challenge))
+            .await
+            .map_err(|e| format!("Failed to send auth challenge: {}", e))?;
+
+        let response = timeout(AUTH_HANDSHAKE_TIMEOUT, ws_receiver.next())
+            .await
+            .map_err(|_| "Auth response timed out".to_string())?
+            .ok_or_else(|| "Connection closed before authenticating".to_string())?
+            .map_err(|e| format!("WebSocket error while awaiting auth response: {}", e))?;
+
+        let Message::Text(text) = response else {
+            return Err("Expected a text AUTH_RESPONSE message".to_string());
+        };
+
+        let json_msg: serde_json::Value = serde_json::from_str(&text)
+            .map_err(|e| format!("Invalid AUTH_RESPONSE JSON: {}", e))?;
+
+        if json_msg.get("type").and_then(|t| t.as_str()) != Some("AUTH_RESPONSE") {
+            return Err("Expected message type AUTH_RESPONSE".to_string());
+        }
+
+        let pubkey_b64 = json_msg.get("pubkey")
+            .and_then(|p| p.as_str())
+            .ok_or("Missing pubkey in AUTH_RESPONSE")?
+            .to_string();
+
+        let sig_b64 = json_msg.get("sig")
+            .and_then(|s| s.as_str())
+            .ok_or("Missing sig in AUTH_RESPONSE")?;
+
+        if !config.ea_public_keys.values().any(|allowed| allowed == &pubkey_b64) {
+            return Err("Public key is not in the configured allowlist".to_string());
+        }
+
+        let pubkey_bytes: [u8; 32] = BASE64.decode(&pubkey_b64)
+            .map_err(|e| format!("Invalid pubkey encoding: {}", e))?
+            .try_into()
+            .map_err(|_| "pubkey must be 32 bytes".to_string())?;
+        let verifying_key = VerifyingKey::from_bytes(&pubkey_bytes)
+            .map_err(|e| format!("Invalid ed25519 public key: {}", e))?;
+
+        let sig_bytes: [u8; 64] = BASE64.decode(sig_b64)
+            .map_err(|e| format!("Invalid signature encoding: {}", e))?
+            .try_into()
+            .map_err(|_| "signature must be 64 bytes".to_string())?;
+        let signature = Signature::from_bytes(&sig_bytes);
+
+        let mut signed_payload = nonce.to_vec();
+        signed_payload.extend_from_slice(config.server_id.as_bytes());
+
+        verifying_key
+            .verify(&signed_payload, &signature)
+            .map_err(|_| "Signature verification failed".to_string())?;
+
+        Ok(pubkey_b64)
+    }
+
+    /// 受信テキストを`RequestContainer`として解釈し、`handle_request`へディスパッチする。
+    /// 解析に失敗した場合や業務エラーは、機械可読な`code`を持つ`ResponseKind::Error`を
+    /// JSON化して`Err`に詰めて返す（呼び出し側はこれをそのままクライアントへ送り返せる）。
     async fn process_message(
         message: &str,
         client_id: &str,
         clients: &Arc<RwLock<HashMap<String, ClientConnection>>>,
         config: &WSServerConfig,
+        session_store: &Option<Arc<SessionStore>>,
+        client_senders: &Arc<RwLock<HashMap<String, mpsc::UnboundedSender<Message>>>>,
     ) -> Result<Option<String>, String> {
-        // JSONメッセージを解析
-        let json_msg: serde_json::Value = serde_json::from_str(message)
-            .map_err(|e| format!("Invalid JSON: {}", e))?;
+        let container: RequestContainer = serde_json::from_str(message)
+            .map_err(|e| ResponseContainer::error(Uuid::new_v4(), "INVALID_REQUEST", format!("Malformed request: {}", e)).to_json())?;
 
-        let msg_type = json_msg.get("type")
-            .and_then(|t| t.as_str())
-            .ok_or("Missing message type")?;
+        let request_id = container.request_id;
 
-        match msg_type {
-            "AUTH" => {
-                Self::handle_auth_message(&json_msg, client_id, clients, config).await
-            }
-            "HEARTBEAT" => {
-                Self::handle_heartbeat_message(client_id, clients).await
-            }
-            "OPENED" | "CLOSED" | "ERROR" | "PRICE" | "PONG" | "INFO" => {
-                // EA からのイベントメッセージ
-                Self::handle_ea_event_message(&json_msg, client_id, clients).await
+        match Self::handle_request(container.kind, client_id, clients, config, session_store, client_senders).await {
+            Ok(Some(kind)) => Ok(Some(ResponseContainer { request_id, kind }.to_json())),
+            Ok(None) => Ok(None),
+            Err((code, message)) => Err(ResponseContainer::error(request_id, code, message).to_json()),
+        }
+    }
+
+    async fn handle_request(
+        kind: RequestKind,
+        client_id: &str,
+        clients: &Arc<RwLock<HashMap<String, ClientConnection>>>,
+        config: &WSServerConfig,
+        session_store: &Option<Arc<SessionStore>>,
+        client_senders: &Arc<RwLock<HashMap<String, mpsc::UnboundedSender<Message>>>>,
+    ) -> Result<Option<ResponseKind>, (String, String)> {
+        match kind {
+            RequestKind::Heartbeat => Self::handle_heartbeat_message(client_id, clients).await,
+            RequestKind::Authenticate => Err((
+                "ALREADY_AUTHENTICATED".to_string(),
+                "Authentication is completed via the ed25519 handshake, not this request kind".to_string(),
+            )),
+            RequestKind::RegisterEa { ea_info } => {
+                Self::handle_register_ea_message(ea_info, client_id, clients, config, session_store, client_senders).await
             }
-            _ => {
-                Err(format!("Unknown message type: {}", msg_type))
+            RequestKind::OrderUpdate { payload } => Self::handle_order_update_message(payload, client_id, clients).await,
+            RequestKind::Subscribe { channels } => Self::handle_subscribe_message(channels, client_id, clients).await,
+            RequestKind::EaEvent { event_type, payload } => {
+                Self::handle_ea_event_message(&event_type, &payload, client_id, clients, session_store).await
             }
         }
     }
 
-    async fn handle_auth_message(
-        json_msg: &serde_json::Value,
+    /// 認証済みであることを要求する共通チェック
+    async fn require_authenticated(
+        client_id: &str,
+        clients: &Arc<RwLock<HashMap<String, ClientConnection>>>,
+    ) -> Result<(), (String, String)> {
+        let clients_lock = clients.read().await;
+        match clients_lock.get(client_id) {
+            Some(client) if client.authenticated => Ok(()),
+            Some(_) => Err(("NOT_AUTHENTICATED".to_string(), "Client has not completed the auth handshake".to_string())),
+            None => Err(("CLIENT_NOT_FOUND".to_string(), "Client not found".to_string())),
+        }
+    }
+
+    // ed25519ハンドシェイクで既に認証済みのクライアントから送られるEAメタデータを登録する。
+    // トークン照合はしない（認証は`perform_auth_handshake`で完了している）。
+    // 永続化が有効なら、resume_window内の再接続を検出してセッション行をupsertする。
+    async fn handle_register_ea_message(
+        ea_info: EAInfo,
         client_id: &str,
         clients: &Arc<RwLock<HashMap<String, ClientConnection>>>,
         config: &WSServerConfig,
-    ) -> Result<Option<String>, String> {
-        let token = json_msg.get("token")
-            .and_then(|t| t.as_str())
-            .ok_or("Missing auth token")?;
-
-        if token != config.auth_token {
-            return Err("Invalid auth token".to_string());
-        }
-
-        // EA情報を取得
-        let ea_info = json_msg.get("eaInfo")
-            .map(|info| EAInfo {
-                version: info.get("version").and_then(|v| v.as_str()).unwrap_or("unknown").to_string(),
-                platform: info.get("platform").and_then(|p| p.as_str()).unwrap_or("unknown").to_string(),
-                account: info.get("account").and_then(|a| a.as_str()).unwrap_or("unknown").to_string(),
-                server_name: info.get("serverName").and_then(|s| s.as_str()).map(|s| s.to_string()),
-                company_name: info.get("companyName").and_then(|c| c.as_str()).map(|c| c.to_string()),
-            });
+        session_store: &Option<Arc<SessionStore>>,
+        client_senders: &Arc<RwLock<HashMap<String, mpsc::UnboundedSender<Message>>>>,
+    ) -> Result<Option<ResponseKind>, (String, String)> {
+        Self::require_authenticated(client_id, clients).await?;
 
-        // クライアントを認証済みに更新
-        {
+        let pubkey = {
             let mut clients_lock = clients.write().await;
-            if let Some(client) = clients_lock.get_mut(client_id) {
-                client.authenticated = true;
-                client.ea_info = ea_info.clone();
-            }
-        }
+            let client = clients_lock.get_mut(client_id).ok_or((
+                "CLIENT_NOT_FOUND".to_string(),
+                "Client not found".to_string(),
+            ))?;
+            client.ea_info = Some(ea_info.clone());
+            client.pubkey.clone().unwrap_or_default()
+        };
 
-        info!("Client {} authenticated: {:?}", client_id, ea_info);
+        info!("Client {} registered EA info: {:?}", client_id, ea_info);
+
+        if let Some(store) = session_store {
+            let resume_window = config
+                .persistence
+                .as_ref()
+                .map(|p| p.resume_window_seconds)
+                .unwrap_or(0);
+            match store.find_resumable_session(&ea_info.account, resume_window).await {
+                Ok(Some(previous)) if !previous.pending_messages.is_empty() => {
+                    // `find_resumable_session`は取り出した時点で`pending_messages`テーブルから
+                    // 該当行を削除済みのため、ここで確実にこの接続のoutbound_tx経由で配信しないと
+                    // 再接続したEAがキュー済みの注文指示を永久に失う
+                    let senders = client_senders.read().await;
+                    if let Some(sender) = senders.get(client_id) {
+                        let mut delivered = 0;
+                        for message in &previous.pending_messages {
+                            if sender.send(Message::Text(message.clone())).is_ok() {
+                                delivered += 1;
+                            }
+                        }
+                        info!(
+                            "Replayed {}/{} buffered message(s) to resumed session for account {}",
+                            delivered,
+                            previous.pending_messages.len(),
+                            ea_info.account
+                        );
+                    } else {
+                        error!(
+                            "Cannot replay {} buffered message(s) for account {}: client {} has no registered sender",
+                            previous.pending_messages.len(),
+                            ea_info.account,
+                            client_id
+                        );
+                    }
+                }
+                Ok(_) => {}
+                Err(e) => error!("Failed to look up resumable session for {}: {}", ea_info.account, e),
+            }
 
-        // 認証成功レスポンス
-        let response = serde_json::json!({
-            "type": "AUTH_SUCCESS",
-            "timestamp": chrono::Utc::now().to_rfc3339(),
-            "clientId": client_id
-        });
+            if let Err(e) = store.upsert_session(&ea_info.account, &pubkey, client_id).await {
+                error!("Failed to persist session for account {}: {}", ea_info.account, e);
+            }
+        }
 
-        Ok(Some(response.to_string()))
+        Ok(Some(ResponseKind::RegisterEaAck))
     }
 
     async fn handle_heartbeat_message(
         client_id: &str,
         clients: &Arc<RwLock<HashMap<String, ClientConnection>>>,
-    ) -> Result<Option<String>, String> {
+    ) -> Result<Option<ResponseKind>, (String, String)> {
         // heartbeatを更新
         {
             let mut clients_lock = clients.write().await;
@@ -723,37 +1856,62 @@ impl WSServerManager {
             }
         }
 
-        // heartbeat応答
-        let response = serde_json::json!({
-            "type": "HEARTBEAT_ACK",
-            "timestamp": chrono::Utc::now().to_rfc3339()
-        });
+        Ok(Some(ResponseKind::HeartbeatAck))
+    }
+
+    async fn handle_order_update_message(
+        payload: serde_json::Value,
+        client_id: &str,
+        clients: &Arc<RwLock<HashMap<String, ClientConnection>>>,
+    ) -> Result<Option<ResponseKind>, (String, String)> {
+        Self::require_authenticated(client_id, clients).await?;
+
+        debug!("Order update from {}: {}", client_id, payload);
 
-        Ok(Some(response.to_string()))
+        Ok(Some(ResponseKind::OrderUpdateAck))
+    }
+
+    async fn handle_subscribe_message(
+        channels: Vec<String>,
+        client_id: &str,
+        clients: &Arc<RwLock<HashMap<String, ClientConnection>>>,
+    ) -> Result<Option<ResponseKind>, (String, String)> {
+        Self::require_authenticated(client_id, clients).await?;
+
+        info!("Client {} subscribed to channels: {:?}", client_id, channels);
+
+        Ok(Some(ResponseKind::SubscribeAck))
     }
 
     async fn handle_ea_event_message(
-        json_msg: &serde_json::Value,
+        event_type: &str,
+        payload: &serde_json::Value,
         client_id: &str,
         clients: &Arc<RwLock<HashMap<String, ClientConnection>>>,
-    ) -> Result<Option<String>, String> {
-        // クライアントが認証済みかチェック
-        {
-            let clients_lock = clients.read().await;
-            if let Some(client) = clients_lock.get(client_id) {
-                if !client.authenticated {
-                    return Err("Client not authenticated".to_string());
-                }
-            } else {
-                return Err("Client not found".to_string());
+        session_store: &Option<Arc<SessionStore>>,
+    ) -> Result<Option<ResponseKind>, (String, String)> {
+        Self::require_authenticated(client_id, clients).await?;
+
+        // EAイベントメッセージを処理（実際の処理はTypeScript側のMessageProcessorで行う）
+        debug!("EA event '{}' from {}: {}", event_type, client_id, payload);
+
+        // 永続化が有効なら、監査・再生用に追記専用のイベントログへ記録する
+        if let Some(store) = session_store {
+            let account = clients
+                .read()
+                .await
+                .get(client_id)
+                .and_then(|c| c.ea_info.as_ref())
+                .map(|i| i.account.clone());
+            if let Err(e) = store
+                .record_event(client_id, account.as_deref(), event_type, &payload.to_string())
+                .await
+            {
+                error!("Failed to record EA event '{}' from {}: {}", event_type, client_id, e);
             }
         }
 
-        // EAイベントメッセージを処理（実際の処理はTypeScript側のMessageProcessorで行う）
-        debug!("EA event from {}: {}", client_id, json_msg);
-        
-        // このメッセージは応答不要
-        Ok(None)
+        Ok(Some(ResponseKind::EventAck))
     }
 }
 
@@ -763,7 +1921,7 @@ impl WSServerManager {
 pub async fn start_websocket_server(
     port: u16,
     host: Option<String>,
-    auth_token: Option<String>,
+    ea_public_keys: Option<HashMap<String, String>>,
     state: State<'_, WSServerManager>,
 ) -> Result<(), String> {
     // 設定を更新
@@ -773,8 +1931,8 @@ pub async fn start_websocket_server(
         if let Some(h) = host {
             config.host = h;
         }
-        if let Some(token) = auth_token {
-            config.auth_token = token;
+        if let Some(keys) = ea_public_keys {
+            config.ea_public_keys = keys;
         }
     }
 
@@ -788,6 +1946,14 @@ pub async fn stop_websocket_server(
     state.stop_server().await
 }
 
+#[tauri::command]
+pub async fn stop_websocket_server_graceful(
+    timeout_seconds: Option<u64>,
+    state: State<'_, WSServerManager>,
+) -> Result<(), String> {
+    state.stop_server_graceful(timeout_seconds).await
+}
+
 #[tauri::command]
 pub async fn get_websocket_server_status(
     state: State<'_, WSServerManager>,
@@ -810,11 +1976,100 @@ pub async fn disconnect_websocket_client(
     state.disconnect_client(&client_id).await
 }
 
+/// 指定したEAへコマンドを送り、`requestId`で相関した応答を待つ。タイムアウトと
+/// クライアント切断は異なるエラーメッセージとして区別される。
+#[tauri::command]
+pub async fn send_command_to_client(
+    client_id: String,
+    command: serde_json::Value,
+    timeout_seconds: Option<u64>,
+    state: State<'_, WSServerManager>,
+) -> Result<serde_json::Value, String> {
+    state.send_command(&client_id, command, timeout_seconds.unwrap_or(10)).await
+}
+
+/// 監査・再生用に記録されたイベントを、クライアント単位で問い合わせる。
+/// 永続化が無効な場合は空配列を返す。`from`/`to`はRFC3339文字列で指定する。
+#[tauri::command]
+pub async fn query_ea_events(
+    client_id: String,
+    from: Option<String>,
+    to: Option<String>,
+    msg_types: Option<Vec<String>>,
+    state: State<'_, WSServerManager>,
+) -> Result<Vec<StoredEvent>, String> {
+    let from = from
+        .map(|s| DateTime::parse_from_rfc3339(&s).map(|dt| dt.with_timezone(&Utc)))
+        .transpose()
+        .map_err(|e| format!("Invalid `from` timestamp: {}", e))?;
+    let to = to
+        .map(|s| DateTime::parse_from_rfc3339(&s).map(|dt| dt.with_timezone(&Utc)))
+        .transpose()
+        .map_err(|e| format!("Invalid `to` timestamp: {}", e))?;
+
+    state.query_events(&client_id, from, to, msg_types).await
+}
+
+/// クライアントの過去イベントを時系列順で返す。再生間隔の計算・タイマー付き配信は
+/// フロントエンド側の責務とし、このコマンドは順序付きの全イベントを一括で返すのみ。
+#[tauri::command]
+pub async fn replay_events(
+    client_id: String,
+    state: State<'_, WSServerManager>,
+) -> Result<Vec<StoredEvent>, String> {
+    state.replay_events(&client_id).await
+}
+
+#[tauri::command]
+pub async fn pause_websocket_accepting(
+    state: State<'_, WSServerManager>,
+) -> Result<(), String> {
+    state.pause_accepting();
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn resume_websocket_accepting(
+    state: State<'_, WSServerManager>,
+) -> Result<(), String> {
+    state.resume_accepting();
+    Ok(())
+}
+
+/// TCP WebSocketリスナーとは独立に、Unixドメインソケット(Unix)・名前付きパイプ(Windows)
+/// 経由のローカルIPCサーバーを起動する。同一ホストで動くEAはTCPポートを使わずに接続できる。
+#[tauri::command]
+pub async fn start_local_ipc_server(
+    path: String,
+    state: State<'_, WSServerManager>,
+) -> Result<(), String> {
+    state.start_local_ipc_server(path).await
+}
+
+#[tauri::command]
+pub async fn stop_local_ipc_server(
+    state: State<'_, WSServerManager>,
+) -> Result<(), String> {
+    state.stop_local_ipc_server().await
+}
+
+#[tauri::command]
+pub async fn is_websocket_accepting(
+    state: State<'_, WSServerManager>,
+) -> Result<bool, String> {
+    Ok(state.is_accepting())
+}
+
 #[tauri::command]
 pub async fn update_websocket_config(
     config: WSServerConfig,
     state: State<'_, WSServerManager>,
 ) -> Result<(), String> {
+    // TLSが設定されている場合は、保存前に証明書/秘密鍵が正しくパースできることを検証する
+    if let Some(tls_config) = &config.tls {
+        crate::tls::build_tls_acceptor(tls_config)?;
+    }
+
     let mut current_config = state.config.write().await;
     *current_config = config;
     Ok(())
@@ -826,7 +2081,8 @@ pub async fn update_websocket_config(
 pub async fn get_websocket_performance_metrics(
     state: State<'_, WSServerManager>,
 ) -> Result<PerformanceMetrics, String> {
-    Ok(state.connection_pool.get_performance_metrics().await)
+    let uptime_seconds = state.uptime_seconds().await;
+    Ok(state.connection_pool.get_performance_metrics(uptime_seconds).await)
 }
 
 #[tauri::command]
@@ -843,30 +2099,49 @@ pub async fn broadcast_websocket_message(
     message: String,
     state: State<'_, WSServerManager>,
 ) -> Result<usize, String> {
-    state.connection_pool.broadcast_message(&message).await
+    state.broadcast_message(&message).await
 }
 
 #[tauri::command]
 pub async fn optimize_websocket_performance(
     state: State<'_, WSServerManager>,
 ) -> Result<String, String> {
-    let metrics = state.connection_pool.get_performance_metrics().await;
-    
+    let uptime_seconds = state.uptime_seconds().await;
+    let metrics = state.connection_pool.get_performance_metrics(uptime_seconds).await;
+    let open_connections = state.clients.read().await.len();
+    let health = state.system_health.snapshot(open_connections).await;
+    let memory_warning_threshold_mb = state.config.read().await.memory_warning_threshold_mb;
+
     let mut optimizations = Vec::new();
-    
+
     // パフォーマンス最適化の提案
     if metrics.avg_latency_ms > 100.0 {
         optimizations.push("Consider reducing message size or frequency".to_string());
     }
-    
+
     if metrics.error_rate > 5.0 {
         optimizations.push("Check network stability and message format validation".to_string());
     }
-    
+
     if metrics.peak_connections > 50 {
         optimizations.push("Consider implementing connection pooling optimizations".to_string());
     }
-    
+
+    let memory_rss_mb = health.memory_rss_bytes / 1024 / 1024;
+    if memory_rss_mb > memory_warning_threshold_mb {
+        optimizations.push(format!(
+            "Process RSS ({} MB) exceeds the configured threshold ({} MB); investigate memory growth",
+            memory_rss_mb, memory_warning_threshold_mb
+        ));
+    }
+
+    if health.cpu_usage_percent > CPU_PEGGED_THRESHOLD_PERCENT {
+        optimizations.push(format!(
+            "CPU usage is pegged at {:.1}%; consider reducing load or scaling out",
+            health.cpu_usage_percent
+        ));
+    }
+
     if optimizations.is_empty() {
         Ok("WebSocket performance is optimal".to_string())
     } else {
@@ -879,14 +2154,19 @@ pub async fn get_websocket_detailed_stats(
     state: State<'_, WSServerManager>,
 ) -> Result<serde_json::Value, String> {
     let server_stats = state.get_status().await;
-    let performance_metrics = state.connection_pool.get_performance_metrics().await;
+    let uptime_seconds = state.uptime_seconds().await;
+    let performance_metrics = state.connection_pool.get_performance_metrics(uptime_seconds).await;
     let clients = state.clients.read().await;
     
     let connection_qualities: std::collections::HashMap<String, String> = clients
         .iter()
         .map(|(id, client)| (id.clone(), client.connection_quality.clone()))
         .collect();
-    
+    let open_connections = clients.len();
+    drop(clients);
+
+    let health = state.system_health.snapshot(open_connections).await;
+
     let detailed_stats = serde_json::json!({
         "server": server_stats,
         "performance": {
@@ -899,11 +2179,219 @@ pub async fn get_websocket_detailed_stats(
         },
         "connection_qualities": connection_qualities,
         "system_health": {
-            "memory_usage": "optimal", // TODO: 実際のメモリ使用量を計測
-            "cpu_usage": "normal",     // TODO: 実際のCPU使用量を計測
-            "network_status": "stable"
+            "memory_rss_bytes": health.memory_rss_bytes,
+            "cpu_usage_percent": health.cpu_usage_percent,
+            "open_connections": health.open_connections
         }
     });
-    
+
     Ok(detailed_stats)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ed25519_dalek::{Signer, SigningKey};
+    use std::pin::Pin;
+    use std::task::{Context, Poll};
+
+    /// `perform_auth_handshake`の`ws_sender`側として渡すテスト用Sink。送信されたメッセージを
+    /// そのまま`tokio::sync::mpsc`経由でテストコードへ転送するだけで、実際のソケットは使わない。
+    struct MockSender(mpsc::UnboundedSender<Message>);
+
+    impl futures_util::Sink<Message> for MockSender {
+        type Error = std::convert::Infallible;
+
+        fn poll_ready(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+            Poll::Ready(Ok(()))
+        }
+
+        fn start_send(self: Pin<&mut Self>, item: Message) -> Result<(), Self::Error> {
+            let _ = self.get_mut().0.send(item);
+            Ok(())
+        }
+
+        fn poll_flush(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+            Poll::Ready(Ok(()))
+        }
+
+        fn poll_close(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+            Poll::Ready(Ok(()))
+        }
+    }
+
+    /// `perform_auth_handshake`の`ws_receiver`側として渡すテスト用Stream。テストコードが
+    /// `tokio::sync::mpsc`経由で送り込んだメッセージを`Ok(..)`として流すだけ。
+    struct MockReceiver(mpsc::UnboundedReceiver<Message>);
+
+    impl futures_util::Stream for MockReceiver {
+        type Item = Result<Message, tokio_tungstenite::tungstenite::Error>;
+
+        fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+            self.get_mut().0.poll_recv(cx).map(|opt| opt.map(Ok))
+        }
+    }
+
+    fn test_config(server_id: &str, allowlist: HashMap<String, String>) -> WSServerConfig {
+        WSServerConfig {
+            port: 0,
+            host: "127.0.0.1".to_string(),
+            server_id: server_id.to_string(),
+            ea_public_keys: allowlist,
+            max_connections: 10,
+            heartbeat_interval_seconds: 30,
+            connection_timeout_seconds: 300,
+            shutdown_timeout_seconds: 10,
+            max_connections_per_second: 5,
+            max_messages_per_second: 50,
+            persistence: None,
+            metrics_export: None,
+            memory_warning_threshold_mb: 512,
+            tls: None,
+            disabled: false,
+        }
+    }
+
+    /// サーバーが送った`AUTH_CHALLENGE`からnonceを取り出す
+    fn extract_nonce(challenge: &Message) -> Vec<u8> {
+        let Message::Text(text) = challenge else {
+            panic!("expected a text AUTH_CHALLENGE message");
+        };
+        let json: serde_json::Value = serde_json::from_str(text).expect("challenge must be valid JSON");
+        let nonce_b64 = json.get("nonce").and_then(|n| n.as_str()).expect("challenge must carry a nonce");
+        BASE64.decode(nonce_b64).expect("nonce must be valid base64")
+    }
+
+    #[tokio::test]
+    async fn perform_auth_handshake_accepts_valid_signature_from_allowlisted_key() {
+        let signing_key = SigningKey::generate(&mut rand::rngs::OsRng);
+        let pubkey_b64 = BASE64.encode(signing_key.verifying_key().to_bytes());
+
+        let mut allowlist = HashMap::new();
+        allowlist.insert("EA1".to_string(), pubkey_b64.clone());
+        let config = test_config("test-server", allowlist);
+
+        let (challenge_tx, mut challenge_rx) = mpsc::unbounded_channel::<Message>();
+        let (response_tx, response_rx) = mpsc::unbounded_channel::<Message>();
+        let mut sender = MockSender(challenge_tx);
+        let mut receiver = MockReceiver(response_rx);
+        let config_for_task = config.clone();
+
+        let handshake = tokio::spawn(async move {
+            WSServerManager::perform_auth_handshake(&mut sender, &mut receiver, &config_for_task).await
+        });
+
+        let challenge = challenge_rx.recv().await.expect("server must send an AUTH_CHALLENGE");
+        let nonce = extract_nonce(&challenge);
+
+        let mut signed_payload = nonce;
+        signed_payload.extend_from_slice(config.server_id.as_bytes());
+        let signature = signing_key.sign(&signed_payload);
+
+        let response = serde_json::json!({
+            "type": "AUTH_RESPONSE",
+            "pubkey": pubkey_b64,
+            "sig": BASE64.encode(signature.to_bytes()),
+        }).to_string();
+        response_tx.send(Message::Text(response)).expect("test channel must accept the response");
+
+        let result = handshake.await.expect("handshake task must not panic");
+        assert_eq!(result.as_deref(), Ok(pubkey_b64.as_str()));
+    }
+
+    #[tokio::test]
+    async fn perform_auth_handshake_rejects_signature_from_unlisted_key() {
+        let signing_key = SigningKey::generate(&mut rand::rngs::OsRng);
+        let pubkey_b64 = BASE64.encode(signing_key.verifying_key().to_bytes());
+
+        // アロウリストには登録しない
+        let config = test_config("test-server", HashMap::new());
+
+        let (challenge_tx, mut challenge_rx) = mpsc::unbounded_channel::<Message>();
+        let (response_tx, response_rx) = mpsc::unbounded_channel::<Message>();
+        let mut sender = MockSender(challenge_tx);
+        let mut receiver = MockReceiver(response_rx);
+        let config_for_task = config.clone();
+
+        let handshake = tokio::spawn(async move {
+            WSServerManager::perform_auth_handshake(&mut sender, &mut receiver, &config_for_task).await
+        });
+
+        let challenge = challenge_rx.recv().await.expect("server must send an AUTH_CHALLENGE");
+        let nonce = extract_nonce(&challenge);
+
+        let mut signed_payload = nonce;
+        signed_payload.extend_from_slice(config.server_id.as_bytes());
+        let signature = signing_key.sign(&signed_payload);
+
+        let response = serde_json::json!({
+            "type": "AUTH_RESPONSE",
+            "pubkey": pubkey_b64,
+            "sig": BASE64.encode(signature.to_bytes()),
+        }).to_string();
+        response_tx.send(Message::Text(response)).expect("test channel must accept the response");
+
+        let result = handshake.await.expect("handshake task must not panic");
+        assert!(result.is_err(), "a public key outside the allowlist must be rejected");
+    }
+
+    #[tokio::test]
+    async fn perform_auth_handshake_rejects_forged_signature_from_allowlisted_key() {
+        let signing_key = SigningKey::generate(&mut rand::rngs::OsRng);
+        let pubkey_b64 = BASE64.encode(signing_key.verifying_key().to_bytes());
+        let forger_key = SigningKey::generate(&mut rand::rngs::OsRng);
+
+        let mut allowlist = HashMap::new();
+        allowlist.insert("EA1".to_string(), pubkey_b64.clone());
+        let config = test_config("test-server", allowlist);
+
+        let (challenge_tx, mut challenge_rx) = mpsc::unbounded_channel::<Message>();
+        let (response_tx, response_rx) = mpsc::unbounded_channel::<Message>();
+        let mut sender = MockSender(challenge_tx);
+        let mut receiver = MockReceiver(response_rx);
+        let config_for_task = config.clone();
+
+        let handshake = tokio::spawn(async move {
+            WSServerManager::perform_auth_handshake(&mut sender, &mut receiver, &config_for_task).await
+        });
+
+        let challenge = challenge_rx.recv().await.expect("server must send an AUTH_CHALLENGE");
+        let nonce = extract_nonce(&challenge);
+
+        // nonceには正しく署名するが、署名する鍵自体がアロウリストの公開鍵と対応していない
+        let mut signed_payload = nonce;
+        signed_payload.extend_from_slice(config.server_id.as_bytes());
+        let forged_signature = forger_key.sign(&signed_payload);
+
+        let response = serde_json::json!({
+            "type": "AUTH_RESPONSE",
+            "pubkey": pubkey_b64,
+            "sig": BASE64.encode(forged_signature.to_bytes()),
+        }).to_string();
+        response_tx.send(Message::Text(response)).expect("test channel must accept the response");
+
+        let result = handshake.await.expect("handshake task must not panic");
+        assert!(result.is_err(), "a signature not matching the claimed public key must be rejected");
+    }
+
+    #[tokio::test]
+    async fn perform_auth_handshake_times_out_when_no_response_is_sent() {
+        let config = test_config("test-server", HashMap::new());
+
+        let (challenge_tx, mut challenge_rx) = mpsc::unbounded_channel::<Message>();
+        // レスポンス送信側は保持したまま何も送らず、接続は開いたまま応答が二度と来ない
+        // コネクションを模す（`AUTH_HANDSHAKE_TIMEOUT`経過で失敗する）
+        let (_response_tx, response_rx) = mpsc::unbounded_channel::<Message>();
+        let mut sender = MockSender(challenge_tx);
+        let mut receiver = MockReceiver(response_rx);
+
+        let handshake = tokio::spawn(async move {
+            WSServerManager::perform_auth_handshake(&mut sender, &mut receiver, &config).await
+        });
+
+        challenge_rx.recv().await.expect("server must send an AUTH_CHALLENGE");
+
+        let result = handshake.await.expect("handshake task must not panic");
+        assert!(result.is_err(), "no response within AUTH_HANDSHAKE_TIMEOUT must fail the handshake");
+    }
 }
\ No newline at end of file